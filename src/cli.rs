@@ -0,0 +1,110 @@
+// Just enough command line handling for `--name value` style flags. Flags may
+// be repeated, and a flag followed by another flag (or nothing) is a switch.
+use std::ops::RangeInclusive;
+
+pub struct Flags {
+    flags: Vec<(String, Option<String>)>,
+}
+
+impl Flags {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut flags = vec![];
+        let mut args = args.iter().peekable();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("Expected a --flag, buddy, not {}", arg))?;
+            let value = match args.peek() {
+                Some(next) if !next.starts_with("--") => args.next().cloned(),
+                _ => None,
+            };
+            flags.push((String::from(name), value));
+        }
+        Ok(Self { flags })
+    }
+
+    pub fn only(self, known: &[&str]) -> Result<Self, String> {
+        match self
+            .flags
+            .iter()
+            .find(|(name, _)| !known.contains(&name.as_str()))
+        {
+            Some((name, _)) => Err(format!(
+                "Unknown flag --{}, try one of: --{}",
+                name,
+                known.join(", --")
+            )),
+            None => Ok(self),
+        }
+    }
+
+    pub fn switch(&self, name: &str) -> bool {
+        self.flags.iter().any(|(flag, _)| flag == name)
+    }
+
+    pub fn values<'a>(&'a self, name: &str) -> impl Iterator<Item = Result<&'a str, String>> {
+        let name = String::from(name);
+        self.flags
+            .iter()
+            .filter(move |(flag, _)| *flag == name)
+            .map(|(flag, value)| {
+                value
+                    .as_deref()
+                    .ok_or_else(|| format!("--{} needs a value", flag))
+            })
+    }
+
+    pub fn value(&self, name: &str) -> Result<Option<&str>, String> {
+        self.values(name).last().transpose()
+    }
+}
+
+// Accepts `a..=b`, `a..b` or a lone `a`.
+pub fn parse_range(s: &str) -> Result<RangeInclusive<isize>, String> {
+    let number = |n: &str| {
+        n.trim()
+            .parse::<isize>()
+            .map_err(|_| format!("Ranges look like 1..=5 buddy, not {}", s))
+    };
+    if let Some((from, to)) = s.split_once("..=") {
+        Ok(number(from)?..=number(to)?)
+    } else if let Some((from, to)) = s.split_once("..") {
+        let to = number(to)?
+            .checked_sub(1)
+            .ok_or_else(|| format!("{} is an empty range, buddy", s))?;
+        Ok(number(from)?..=to)
+    } else {
+        let n = number(s)?;
+        Ok(n..=n)
+    }
+}
+
+#[test]
+fn parses_ranges() -> Result<(), String> {
+    assert_eq!(parse_range("1..=5")?, 1..=5);
+    assert_eq!(parse_range("1..5")?, 1..=4);
+    assert_eq!(parse_range("-2..=2")?, -2..=2);
+    assert_eq!(parse_range("3")?, 3..=3);
+    assert!(parse_range("a..b").is_err());
+    assert!(parse_range("0..-9223372036854775808").is_err());
+    Ok(())
+}
+
+#[test]
+fn parses_values_and_switches() -> Result<(), String> {
+    let args = ["--slope", "3,1", "--color", "--slope", "-1,2"]
+        .iter()
+        .map(|s| String::from(*s))
+        .collect::<Vec<_>>();
+    let flags = Flags::parse(&args)?.only(&["slope", "color"])?;
+    assert_eq!(
+        flags.values("slope").collect::<Result<Vec<_>, _>>()?,
+        vec!["3,1", "-1,2"]
+    );
+    assert!(flags.value("color").is_err());
+    assert!(flags.switch("color"));
+    assert!(!flags.switch("top"));
+    assert_eq!(flags.value("top")?, None);
+    assert!(Flags::parse(&args)?.only(&["slope"]).is_err());
+    Ok(())
+}
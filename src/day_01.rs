@@ -67,7 +67,7 @@ fn part1_test_input() -> Result<(), String> {
 675
 1456\
 ";
-    let solution = part1_solution(&input)?;
+    let solution = part1_solution(input)?;
     assert_eq!(solution, 514579);
     Ok(())
 }
@@ -39,7 +39,7 @@ fn password(input: &str) -> IResult<&str, &str> {
     alphanumeric1(input)
 }
 
-fn password_entry<'a>(input: &'a str) -> IResult<&str, PasswordEntry<'a>> {
+fn password_entry<'a>(input: &'a str) -> IResult<&'a str, PasswordEntry<'a>> {
     map(
         tuple((range, tag(" "), required_letter, tag(": "), password)),
         |(range, _, required_letter, _, password)| PasswordEntry {
@@ -92,7 +92,7 @@ pub fn part1_solution(input: &str) -> Result<usize, String> {
         })
         .collect::<Result<Vec<usize>, String>>()?
         .iter()
-        .fold(0, |a, b| a + b))
+        .sum::<usize>())
 }
 
 pub fn part1() -> Result<(), String> {
@@ -113,7 +113,7 @@ pub fn part2_solution(input: &str) -> Result<usize, String> {
         })
         .collect::<Result<Vec<usize>, String>>()?
         .iter()
-        .fold(0, |a, b| a + b))
+        .sum::<usize>())
 }
 
 pub fn part2() -> Result<(), String> {
@@ -1,72 +1,561 @@
-use std::fs;
+use std::{fmt, fs, ops::RangeInclusive, slice, str::FromStr};
 
+use crate::cli::{parse_range, Flags};
+
+mod density;
+mod image;
+mod render;
+mod route;
+mod terrain;
+
+use terrain::{Legend, Tally};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 struct Heading {
-    horizontal: usize,
-    verticle: usize,
+    horizontal: isize,
+    vertical: isize,
 }
 
 impl Heading {
-    fn from(horizontal: usize, verticle: usize) -> Self {
-        Self {
+    fn new(horizontal: isize, vertical: isize) -> Result<Self, String> {
+        if vertical == 0 {
+            return Err(String::from(
+                "A heading has to move up or down, buddy. Vertical step can't be 0.",
+            ));
+        }
+        Ok(Self {
             horizontal,
-            verticle,
+            vertical,
+        })
+    }
+}
+
+impl FromStr for Heading {
+    type Err = String;
+
+    // Slopes are written `right,down`, e.g. `3,1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad_slope = || format!("Slopes look like 3,1 buddy, not {}", s);
+        let (horizontal, vertical) = s.split_once(',').ok_or_else(bad_slope)?;
+        Heading::new(
+            horizontal.trim().parse().map_err(|_| bad_slope())?,
+            vertical.trim().parse().map_err(|_| bad_slope())?,
+        )
+    }
+}
+
+impl fmt::Display for Heading {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let horizontal = if self.horizontal < 0 { "left" } else { "right" };
+        let vertical = if self.vertical < 0 { "up" } else { "down" };
+        write!(
+            f,
+            "{} {}, {} {}",
+            horizontal,
+            self.horizontal.unsigned_abs(),
+            vertical,
+            self.vertical.unsigned_abs()
+        )
+    }
+}
+
+fn default_headings() -> Result<Vec<Heading>, String> {
+    Ok(vec![
+        Heading::new(1, 1)?,
+        Heading::new(3, 1)?,
+        Heading::new(5, 1)?,
+        Heading::new(7, 1)?,
+        Heading::new(1, 2)?,
+    ])
+}
+
+// One slope per line, blank lines and lines starting with `#` are skipped.
+fn headings_from_file(path: &str) -> Result<Vec<Heading>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|_| format!("bad slopes path, buddy: {}", path))?;
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(Heading::from_str)
+        .collect()
+}
+
+fn headings_from_flags(flags: &Flags) -> Result<Vec<Heading>, String> {
+    let mut headings = vec![];
+    if let Some(path) = flags.value("slopes-file")? {
+        headings.extend(headings_from_file(path)?);
+    }
+    for slope in flags.values("slope") {
+        headings.push(slope?.parse()?);
+    }
+    if headings.is_empty() {
+        return default_headings();
+    }
+    Ok(headings)
+}
+
+// Every command that reads a map understands these.
+const MAP_FLAGS: [&str; 4] = ["map", "boundary", "terrain", "legend"];
+
+fn flags(args: &[String], extra: &[&str]) -> Result<Flags, String> {
+    Flags::parse(args)?.only(&[&MAP_FLAGS[..], extra].concat())
+}
+
+pub fn cli(args: &[String]) -> Result<(), String> {
+    match args.split_first() {
+        None => {
+            part1()?;
+            part2()
         }
+        Some((command, rest)) => match command.as_str() {
+            "part1" => part1_with(&flags(rest, &[])?),
+            "part2" => part2_with(&flags(rest, &["slope", "slopes-file"])?),
+            "search" => search_with(&flags(rest, &["dx", "dy", "top"])?),
+            "render" => render_with(&flags(rest, &["slope", "slopes-file", "color"])?),
+            "export" => export_with(&flags(rest, &["slope", "slopes-file", "out"])?),
+            "route" => route_with(&flags(rest, &["step", "window", "avoid", "color"])?),
+            "query" => query_with(&flags(rest, &["columns", "rows"])?),
+            _ => Err(format!("Unknown day 3 command: {}", command)),
+        },
+    }
+}
+
+// What happens when a heading walks off the edge of the map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Boundary {
+    // The map repeats forever to the left and right, and the trip ends at the
+    // top or bottom.
+    Repeat,
+    // The map wraps in both directions. The trip ends when it gets back to
+    // where it started.
+    Torus,
+    // Walking off the left or right keeps you on the edge column.
+    Clamp,
+    // Like Repeat, but every other copy of the map is flipped left to right.
+    Mirror,
+    // The trip ends as soon as it leaves the map in any direction.
+    Stop,
+}
+
+impl FromStr for Boundary {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "repeat" => Boundary::Repeat,
+            "torus" => Boundary::Torus,
+            "clamp" => Boundary::Clamp,
+            "mirror" => Boundary::Mirror,
+            "stop" => Boundary::Stop,
+            _ => {
+                return Err(format!(
+                    "Boundaries are repeat, torus, clamp, mirror or stop, not {}",
+                    s
+                ))
+            }
+        })
+    }
+}
+
+// Each square holds the index of its terrain in the legend.
+struct Forest {
+    rows: Vec<Vec<usize>>,
+    width: usize,
+    legend: Legend,
+    boundary: Boundary,
+}
+
+impl Forest {
+    #[cfg(test)]
+    fn parse(input: &str) -> Result<Self, String> {
+        Self::parse_with_legend(input, Legend::default())
+    }
+
+    // Every row has to be as wide as the first one and only use symbols from
+    // the legend. Errors point at the offending line and column, counting from
+    // 1.
+    fn parse_with_legend(input: &str, legend: Legend) -> Result<Self, String> {
+        let mut width = None;
+        let rows = input
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| {
+                let row = line
+                    .chars()
+                    .enumerate()
+                    .map(|(column, c)| {
+                        legend.index(c).ok_or_else(|| {
+                            format!(
+                                "Line {}, column {}: {:?} isn't in the legend",
+                                idx + 1,
+                                column + 1,
+                                c
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                match width {
+                    _ if row.is_empty() => Err(format!("Line {} of the map is empty", idx + 1)),
+                    Some(width) if width != row.len() => Err(format!(
+                        "Line {} is {} wide but line 1 is {} wide",
+                        idx + 1,
+                        row.len(),
+                        width
+                    )),
+                    _ => {
+                        width = Some(row.len());
+                        Ok(row)
+                    }
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let width = width.ok_or_else(|| String::from("The map is empty, buddy."))?;
+        Ok(Self {
+            rows,
+            width,
+            legend,
+            boundary: Boundary::Repeat,
+        })
+    }
+
+    fn with_boundary(self, boundary: Boundary) -> Self {
+        Self { boundary, ..self }
+    }
+
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    // Where a position is drawn. Torus and clamp boundaries keep every trip on
+    // the map, the others are drawn on copies of the map tiled left and right.
+    fn display(&self, row: isize, column: isize) -> (isize, isize) {
+        let height = self.height() as isize;
+        let width = self.width() as isize;
+        match self.boundary {
+            Boundary::Torus => (row.rem_euclid(height), column.rem_euclid(width)),
+            Boundary::Clamp => (row, column.clamp(0, width - 1)),
+            _ => (row, column),
+        }
+    }
+
+    // The square a position lands on. rem_euclid keeps leftward headings
+    // wrapping around to the right.
+    fn square(&self, row: isize, column: isize) -> (usize, usize) {
+        let (row, column) = self.display(row, column);
+        let width = self.width() as isize;
+        let column = match self.boundary {
+            Boundary::Mirror => {
+                let column = column.rem_euclid(2 * width);
+                if column < width {
+                    column
+                } else {
+                    2 * width - 1 - column
+                }
+            }
+            _ => column.rem_euclid(width),
+        };
+        (row as usize, column as usize)
+    }
+
+    fn terrain(&self, row: isize, column: isize) -> usize {
+        let (row, column) = self.square(row, column);
+        self.rows[row][column]
+    }
+
+    fn symbol(&self, row: isize, column: isize) -> char {
+        self.legend.terrains[self.terrain(row, column)].symbol
+    }
+
+    // Anything that costs something to run into counts as a hit.
+    fn is_hit(&self, row: isize, column: isize) -> bool {
+        self.legend.terrains[self.terrain(row, column)].cost > 0
+    }
+
+    // The unwrapped (row, column) of every step the heading takes, until the
+    // boundary ends the trip or the next step is too far out to count.
+    // Downward headings start in the top left, upward headings start in the
+    // bottom left, and the starting square itself is never included.
+    fn path<'a>(&'a self, heading: &Heading) -> impl Iterator<Item = (isize, isize)> + 'a {
+        let height = self.height() as isize;
+        let width = self.width() as isize;
+        let start = if heading.vertical > 0 { 0 } else { height - 1 };
+        let Heading {
+            horizontal,
+            vertical,
+        } = *heading;
+        (1..)
+            .map_while(move |step: isize| {
+                let row = start.checked_add(step.checked_mul(vertical)?)?;
+                Some((row, step.checked_mul(horizontal)?))
+            })
+            .take_while(move |(row, column)| match self.boundary {
+                Boundary::Torus => self.display(*row, *column) != (start, 0),
+                Boundary::Stop => (0..height).contains(row) && (0..width).contains(column),
+                _ => (0..height).contains(row),
+            })
+    }
+
+    fn tally(&self, heading: &Heading) -> Tally {
+        let mut tally = Tally::new(&self.legend);
+        for (row, column) in self.path(heading) {
+            tally.add(&self.legend, self.terrain(row, column));
+        }
+        tally
+    }
+
+    // The total cost of the trip, which with the default legend is the number
+    // of trees hit.
+    fn collisions(&self, heading: &Heading) -> usize {
+        self.path(heading)
+            .map(|(row, column)| self.legend.terrains[self.terrain(row, column)].cost)
+            .sum()
+    }
+}
+
+fn forest_from_flags(flags: &Flags) -> Result<Forest, String> {
+    let path = flags.value("map")?.unwrap_or("src/day_03_input.txt");
+    let input = fs::read_to_string(path).map_err(|_| format!("bad map path, buddy: {}", path))?;
+    let boundary = match flags.value("boundary")? {
+        Some(boundary) => boundary.parse()?,
+        None => Boundary::Repeat,
+    };
+    let mut legend = Legend::default();
+    if let Some(path) = flags.value("legend")? {
+        legend.extend_from_file(path)?;
+    }
+    for terrain in flags.values("terrain") {
+        legend.insert(terrain?.parse()?);
+    }
+    Ok(Forest::parse_with_legend(input.trim(), legend)?.with_boundary(boundary))
+}
+
+fn render_with(flags: &Flags) -> Result<(), String> {
+    let forest = forest_from_flags(flags)?;
+    let paths = headings_from_flags(flags)?
+        .iter()
+        .map(|heading| forest.path(heading).collect())
+        .collect::<Vec<_>>();
+    print!("{}", render::render(&forest, &paths, flags.switch("color")));
+    Ok(())
+}
+
+fn export_with(flags: &Flags) -> Result<(), String> {
+    let out = flags
+        .value("out")?
+        .ok_or_else(|| String::from("Where should it go? Pass --out forest.svg or forest.png"))?;
+    let forest = forest_from_flags(flags)?;
+    let paths = headings_from_flags(flags)?
+        .iter()
+        .map(|heading| forest.path(heading).collect())
+        .collect::<Vec<_>>();
+    let bytes = if out.ends_with(".svg") {
+        image::svg(&forest, &paths).into_bytes()
+    } else if out.ends_with(".png") {
+        image::png(&forest, &paths)?
+    } else {
+        return Err(format!("Can only export .svg or .png, not {}", out));
+    };
+    fs::write(out, bytes).map_err(|e| format!("Couldn't write {}: {}", out, e))
+}
+
+fn route_with(flags: &Flags) -> Result<(), String> {
+    let forest = forest_from_flags(flags)?;
+    let mut steps = flags
+        .values("step")
+        .map(|step| route::parse_step(step?))
+        .collect::<Result<Vec<_>, _>>()?;
+    if steps.is_empty() {
+        steps.extend_from_slice(&route::DEFAULT_STEPS);
     }
+    let window = match flags.value("window")? {
+        Some(range) => parse_range(range)?,
+        None => 0..=forest.width() as isize - 1,
+    };
+    let route = route::cheapest_route(&forest, &steps, window, flags.switch("avoid"))?;
+    print!(
+        "{}",
+        render::render(
+            &forest,
+            slice::from_ref(&route.squares),
+            flags.switch("color")
+        )
+    );
+    println!(
+        "{} steps, {}",
+        route.squares.len(),
+        route.tally.describe(&forest.legend)
+    );
+    Ok(())
+}
+
+fn query_with(flags: &Flags) -> Result<(), String> {
+    let forest = forest_from_flags(flags)?;
+    let columns = match flags.value("columns")? {
+        Some(range) => parse_range(range)?,
+        None => 0..=forest.width() as isize - 1,
+    };
+    let rows = match flags.value("rows")? {
+        Some(range) => parse_range(range)?,
+        None => 0..=forest.height() as isize - 1,
+    };
+    let index = density::TreeIndex::new(&forest);
+    let hits = index.count(columns.clone(), rows.clone())?;
+    let span = |range: &RangeInclusive<isize>| {
+        range
+            .end()
+            .checked_sub(*range.start())
+            .and_then(|span| span.checked_add(1))
+            .map(|span| span.max(0))
+    };
+    let squares = span(&columns)
+        .zip(span(&rows))
+        .and_then(|(columns, rows)| columns.checked_mul(rows))
+        .ok_or_else(|| format!("Columns {:?} are too far out to count, buddy", columns))?;
+    println!(
+        "{} of {} squares hit in columns {:?}, rows {:?}",
+        hits, squares, columns, rows
+    );
+    Ok(())
 }
 
 pub fn part1() -> Result<(), String> {
-    let input =
-        fs::read_to_string("src/day_03_input.txt").map_err(|_| String::from("bad path, buddy."))?;
-    let collisions = part1_solution(
-        &input.trim(),
-        Heading {
-            horizontal: 3,
-            verticle: 1,
-        },
-    )?;
-    println!("Part 1: {}", collisions);
+    part1_with(&Flags::parse(&[])?)
+}
+
+fn part1_with(flags: &Flags) -> Result<(), String> {
+    let forest = forest_from_flags(flags)?;
+    println!("Part 1: {}", forest.collisions(&Heading::new(3, 1)?));
     Ok(())
 }
 
-fn part1_solution(input: &str, heading: Heading) -> Result<usize, String> {
-    let mut lines = input.split("\n");
-    let mut collisions = 0;
+#[cfg(test)]
+fn part1_solution(input: &str, heading: &Heading) -> Result<usize, String> {
+    Ok(Forest::parse(input)?.collisions(heading))
+}
 
-    // Always skip the first entry
-    lines.next();
+struct SlopeCollisions {
+    heading: Heading,
+    collisions: usize,
+}
+
+fn slope_collisions(forest: &Forest, headings: &[Heading]) -> Vec<SlopeCollisions> {
+    headings
+        .iter()
+        .map(|heading| SlopeCollisions {
+            heading: *heading,
+            collisions: forest.collisions(heading),
+        })
+        .collect()
+}
+
+struct SearchResult {
+    heading: Heading,
+    collisions: usize,
+    // How many squares the trip landed on.
+    squares: usize,
+}
 
-    let mut horizontal_idx = 0;
-    while let Some(current) = lines.nth(heading.verticle - 1) {
-        horizontal_idx = (horizontal_idx + heading.horizontal) % current.len();
-        if current.chars().nth(horizontal_idx) == Some('#') {
-            collisions += 1;
+// Tries every heading in the given ranges and keeps the `top` with the lowest
+// collision cost per square landed on, so steep headings that skip most of the
+// map don't win just by landing on less of it. Ties go to the longer trip,
+// then the smaller steps. Headings that leave the map straight away are left
+// out.
+fn search(
+    forest: &Forest,
+    horizontal: RangeInclusive<isize>,
+    vertical: RangeInclusive<isize>,
+    top: usize,
+) -> Result<Vec<SearchResult>, String> {
+    let mut slopes = vec![];
+    for dx in horizontal {
+        for dy in vertical.clone().filter(|dy| *dy != 0) {
+            let heading = Heading::new(dx, dy)?;
+            let squares = forest.path(&heading).count();
+            if squares > 0 {
+                slopes.push(SearchResult {
+                    heading,
+                    collisions: forest.collisions(&heading),
+                    squares,
+                });
+            }
         }
     }
+    let steps = |slope: &SearchResult| {
+        (
+            slope.heading.horizontal.unsigned_abs(),
+            slope.heading.vertical.unsigned_abs(),
+        )
+    };
+    slopes.sort_by(|a, b| {
+        (a.collisions * b.squares)
+            .cmp(&(b.collisions * a.squares))
+            .then(b.squares.cmp(&a.squares))
+            .then(steps(a).cmp(&steps(b)))
+    });
+    slopes.truncate(top);
+    Ok(slopes)
+}
 
-    Ok(collisions)
+fn search_with(flags: &Flags) -> Result<(), String> {
+    let forest = forest_from_flags(flags)?;
+    let horizontal = match flags.value("dx")? {
+        Some(range) => parse_range(range)?,
+        None => 1..=forest.width() as isize,
+    };
+    let vertical = match flags.value("dy")? {
+        Some(range) => parse_range(range)?,
+        None => 1..=forest.height() as isize,
+    };
+    let top = match flags.value("top")? {
+        Some(top) => top
+            .parse()
+            .map_err(|_| format!("--top wants a number, not {}", top))?,
+        None => 10,
+    };
+    for (rank, slope) in search(&forest, horizontal, vertical, top)?
+        .iter()
+        .enumerate()
+    {
+        println!(
+            "{:>3}. {}: {} over {} squares",
+            rank + 1,
+            slope.heading,
+            forest.tally(&slope.heading).describe(&forest.legend),
+            slope.squares
+        );
+    }
+    Ok(())
 }
 
 pub fn part2() -> Result<(), String> {
-    let input =
-        fs::read_to_string("src/day_03_input.txt").map_err(|_| String::from("bad path, buddy."))?;
-    let headings = vec![
-        Heading::from(1, 1),
-        Heading::from(3, 1),
-        Heading::from(5, 1),
-        Heading::from(7, 1),
-        Heading::from(1, 2),
-    ];
-    let mut product = 1;
-    for heading in headings.into_iter() {
-        product = product * part1_solution(&input.trim(), heading)?;
-    }
-    println!("Part 1: {}", product);
+    part2_with(&Flags::parse(&[])?)
+}
+
+fn part2_with(flags: &Flags) -> Result<(), String> {
+    let forest = forest_from_flags(flags)?;
+    let slopes = slope_collisions(&forest, &headings_from_flags(flags)?);
+    for slope in slopes.iter() {
+        println!(
+            "  {}: {}",
+            slope.heading,
+            forest.tally(&slope.heading).describe(&forest.legend)
+        );
+    }
+    let product = slopes
+        .iter()
+        .map(|slope| slope.collisions)
+        .product::<usize>();
+    println!("Part 2: {}", product);
     Ok(())
 }
 
-#[test]
-fn part1_test_input() -> Result<(), String> {
-    let input = "\
+#[cfg(test)]
+const TEST_INPUT: &str = "\
 ..##.......
 #...#...#..
 .#....#..#.
@@ -79,13 +568,174 @@ fn part1_test_input() -> Result<(), String> {
 #...##....#
 .#..#...#.#\
 ";
-    let actual = part1_solution(
-        &input,
-        Heading {
-            horizontal: 3,
-            verticle: 1,
-        },
-    )?;
+
+#[test]
+fn part1_test_input() -> Result<(), String> {
+    let actual = part1_solution(TEST_INPUT, &Heading::new(3, 1)?)?;
     assert_eq!(actual, 7);
     Ok(())
 }
+
+#[test]
+fn zero_vertical_heading_is_rejected() {
+    assert!(Heading::new(3, 0).is_err());
+}
+
+#[test]
+fn leftward_and_upward_headings() -> Result<(), String> {
+    // Mirror every row around the first column so that walking left through
+    // the mirrored map visits the same squares as walking right through the
+    // original.
+    let mirrored = TEST_INPUT
+        .split('\n')
+        .map(|line| {
+            let (first, rest) = line.split_at(1);
+            format!("{}{}", first, rest.chars().rev().collect::<String>())
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_eq!(part1_solution(&mirrored, &Heading::new(-3, 1)?)?, 7);
+
+    // Likewise, walking up a map flipped upside down.
+    let flipped = TEST_INPUT.split('\n').rev().collect::<Vec<_>>().join("\n");
+    assert_eq!(part1_solution(&flipped, &Heading::new(3, -1)?)?, 7);
+    Ok(())
+}
+
+#[test]
+fn headings_too_wide_for_isize() -> Result<(), String> {
+    let forest = Forest::parse(TEST_INPUT)?;
+    let widest = Heading::new(isize::MAX, 1)?;
+    assert_eq!(forest.path(&widest).count(), 1);
+    assert_eq!(forest.path(&Heading::new(1, isize::MIN)?).count(), 0);
+    assert_eq!(
+        Heading::new(isize::MIN, 1)?.to_string(),
+        "left 9223372036854775808, down 1"
+    );
+    assert_eq!(
+        search(&forest, isize::MIN..=isize::MIN + 1, 1..=1, 2)?.len(),
+        2
+    );
+    Ok(())
+}
+
+#[test]
+fn part2_test_input() -> Result<(), String> {
+    let slopes = slope_collisions(&Forest::parse(TEST_INPUT)?, &default_headings()?);
+    let collisions = slopes.iter().map(|s| s.collisions).collect::<Vec<_>>();
+    assert_eq!(collisions, vec![2, 7, 3, 4, 2]);
+    assert_eq!(collisions.iter().product::<usize>(), 336);
+    Ok(())
+}
+
+#[test]
+fn parse_heading() -> Result<(), String> {
+    assert_eq!("3,1".parse::<Heading>()?, Heading::new(3, 1)?);
+    assert_eq!(" -1, 2 ".parse::<Heading>()?, Heading::new(-1, 2)?);
+    assert_eq!(Heading::new(-1, 2)?.to_string(), "left 1, down 2");
+    assert!("3,0".parse::<Heading>().is_err());
+    assert!("3".parse::<Heading>().is_err());
+    Ok(())
+}
+
+#[test]
+fn search_test_input() -> Result<(), String> {
+    let forest = Forest::parse(TEST_INPUT)?;
+    let best = search(&forest, 1..=7, 1..=2, 3)?;
+    assert_eq!(best.len(), 3);
+    assert_eq!(best[0].collisions, 0);
+    assert!(best
+        .windows(2)
+        .all(|w| w[0].collisions * w[1].squares <= w[1].collisions * w[0].squares));
+    for slope in best.iter() {
+        assert_eq!(slope.collisions, forest.collisions(&slope.heading));
+        assert_eq!(slope.squares, forest.path(&slope.heading).count());
+    }
+
+    // Going straight down past the bottom lands nowhere, and a steep heading
+    // with no trees loses to a shallow one with no trees over more squares.
+    let best = search(&forest, 0..=0, 1..=20, 20)?;
+    assert!(best.iter().all(|slope| slope.heading.vertical < 11));
+    let best = search(&forest, 1..=3, 1..=10, 1)?;
+    assert_eq!(best[0].collisions, 0);
+    assert!(search(&forest, 1..=3, 1..=10, 100)?
+        .iter()
+        .all(|slope| slope.collisions > 0 || slope.squares <= best[0].squares));
+    Ok(())
+}
+
+#[test]
+fn boundaries() -> Result<(), String> {
+    let forest = Forest::parse(
+        "\
+.#.
+#.#
+.##\
+",
+    )?;
+    let path = |boundary, horizontal, vertical| -> Result<Vec<_>, String> {
+        let forest = Forest::parse("..\n..\n..")?.with_boundary(boundary);
+        Ok(forest
+            .path(&Heading::new(horizontal, vertical)?)
+            .map(|(row, column)| forest.display(row, column))
+            .collect())
+    };
+
+    assert_eq!(path(Boundary::Repeat, 3, 1)?, vec![(1, 3), (2, 6)]);
+    assert_eq!(
+        path(Boundary::Torus, 1, 1)?,
+        vec![(1, 1), (2, 0), (0, 1), (1, 0), (2, 1)]
+    );
+    assert_eq!(path(Boundary::Torus, 0, 1)?, vec![(1, 0), (2, 0)]);
+    assert_eq!(path(Boundary::Clamp, 3, 1)?, vec![(1, 1), (2, 1)]);
+    assert_eq!(path(Boundary::Clamp, -1, 1)?, vec![(1, 0), (2, 0)]);
+    assert_eq!(path(Boundary::Stop, 1, 1)?, vec![(1, 1)]);
+    assert_eq!(path(Boundary::Stop, -1, 1)?, vec![]);
+
+    let mirror = forest.with_boundary(Boundary::Mirror);
+    let row = (-6..9)
+        .map(|column| mirror.symbol(2, column))
+        .collect::<String>();
+    assert_eq!(row, ".####..####..##");
+    assert_eq!(mirror.collisions(&Heading::new(3, 1)?), 1);
+    Ok(())
+}
+
+#[test]
+fn weighted_terrain() -> Result<(), String> {
+    let mut legend = Legend::default();
+    legend.insert("^=rock:5".parse()?);
+    legend.insert("~=ice:0".parse()?);
+    let forest = Forest::parse_with_legend(
+        "\
+.#^~
+~^#.
+^.~#
+#~.^\
+",
+        legend,
+    )?;
+    let heading = Heading::new(1, 1)?;
+    let tally = forest.tally(&heading);
+    assert_eq!(tally.counts, vec![0, 0, 2, 1]);
+    assert_eq!(tally.cost, 10);
+    assert_eq!(forest.collisions(&heading), 10);
+    assert_eq!(tally.describe(&forest.legend), "cost 10 (rock 2, ice 1)");
+    Ok(())
+}
+
+#[test]
+fn malformed_maps() {
+    let error = |input| Forest::parse(input).err().unwrap();
+    assert_eq!(
+        error("..#\n.#\n..#"),
+        "Line 2 is 2 wide but line 1 is 3 wide"
+    );
+    assert_eq!(
+        error("..#\n.#.\n.?#"),
+        "Line 3, column 2: '?' isn't in the legend"
+    );
+    assert_eq!(error("..#\n\n..#"), "Line 2 of the map is empty");
+    assert_eq!(error(""), "The map is empty, buddy.");
+    assert!(Forest::parse("..#\r\n.#.\r\n").is_ok());
+}
@@ -0,0 +1,102 @@
+use std::ops::RangeInclusive;
+
+use super::Forest;
+
+// Summed-area table over one copy of the map, counting the squares a trip
+// would hit (trees, with the default legend). `sums[row][column]` is the count
+// in every square above and to the left of (row, column). Queries treat the
+// map as repeating left and right, whatever its boundary.
+pub struct TreeIndex {
+    sums: Vec<Vec<usize>>,
+    width: isize,
+}
+
+impl TreeIndex {
+    pub fn new(forest: &Forest) -> Self {
+        let (height, width) = (forest.height(), forest.width());
+        let mut sums = vec![vec![0; width + 1]; height + 1];
+        for row in 0..height {
+            for column in 0..width {
+                let hit = forest.rows[row][column];
+                let hit = (forest.legend.terrains[hit].cost > 0) as usize;
+                sums[row + 1][column + 1] =
+                    hit + sums[row][column + 1] + sums[row + 1][column] - sums[row][column];
+            }
+        }
+        Self {
+            sums,
+            width: width as isize,
+        }
+    }
+
+    // Hits in the given rows and every column to the left of `column`, counting
+    // from column 0 (so negative columns give a negative count). None if that's
+    // too many to count.
+    fn left_of(&self, rows: &RangeInclusive<usize>, column: isize) -> Option<isize> {
+        let count = |column: usize| {
+            (self.sums[rows.end() + 1][column] - self.sums[*rows.start()][column]) as isize
+        };
+        let copies = column.div_euclid(self.width);
+        let rest = column.rem_euclid(self.width) as usize;
+        copies
+            .checked_mul(count(self.width as usize))?
+            .checked_add(count(rest))
+    }
+
+    pub fn count(
+        &self,
+        columns: RangeInclusive<isize>,
+        rows: RangeInclusive<isize>,
+    ) -> Result<usize, String> {
+        let height = self.sums.len() as isize - 1;
+        if rows.start() < &0 || rows.end() >= &height {
+            return Err(format!(
+                "Rows have to be within 0..={}, not {:?}",
+                height - 1,
+                rows
+            ));
+        }
+        if rows.is_empty() || columns.is_empty() {
+            return Ok(0);
+        }
+        let rows = *rows.start() as usize..=*rows.end() as usize;
+        let hits = columns
+            .end()
+            .checked_add(1)
+            .and_then(|end| self.left_of(&rows, end))
+            .zip(self.left_of(&rows, *columns.start()))
+            .and_then(|(right, left)| right.checked_sub(left))
+            .ok_or_else(|| format!("Columns {:?} are too far out to count, buddy", columns))?;
+        Ok(hits as usize)
+    }
+}
+
+#[test]
+fn matches_counting_by_hand() -> Result<(), String> {
+    let forest = Forest::parse(super::TEST_INPUT)?;
+    let index = TreeIndex::new(&forest);
+    for columns in [(0, 10), (3, 7), (-15, 2), (5, 40), (-30, -12), (11, 11)].iter() {
+        for rows in [(0, 10), (2, 2), (4, 9)].iter() {
+            let mut expected = 0;
+            for row in rows.0..=rows.1 {
+                for column in columns.0..=columns.1 {
+                    if forest.is_hit(row, column) {
+                        expected += 1;
+                    }
+                }
+            }
+            assert_eq!(
+                index.count(columns.0..=columns.1, rows.0..=rows.1)?,
+                expected,
+                "columns {:?}, rows {:?}",
+                columns,
+                rows
+            );
+        }
+    }
+    assert!(index.count(0..=3, 0..=11).is_err());
+    assert!(index.count(0..=3, -1..=2).is_err());
+    assert!(index.count(0..=isize::MAX, 0..=10).is_err());
+    assert!(index.count(isize::MIN..=0, 0..=10).is_err());
+    Ok(())
+}
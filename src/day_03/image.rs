@@ -0,0 +1,225 @@
+use std::collections::HashSet;
+
+use super::{render::column_span, Boundary, Forest};
+
+const CELL: usize = 10;
+const OPEN: [u8; 3] = [0xf4, 0xf1, 0xe8];
+const TREE: [u8; 3] = [0x2e, 0x6b, 0x34];
+const COLLISION: [u8; 3] = [0xe0, 0x10, 0x10];
+const PATH_COLORS: [[u8; 3]; 6] = [
+    [0x1f, 0x77, 0xb4],
+    [0xff, 0x7f, 0x0e],
+    [0x94, 0x67, 0xbd],
+    [0x17, 0xbe, 0xcf],
+    [0xe3, 0x77, 0xc2],
+    [0xbc, 0xbd, 0x22],
+];
+
+fn hex_color([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// The drawn positions of a path, split wherever a torus boundary wrapped it
+// around to the other side of the map so the lines don't cut across the whole
+// picture.
+fn segments(forest: &Forest, path: &[(isize, isize)]) -> Vec<Vec<(isize, isize)>> {
+    let mut segments = vec![vec![]];
+    for (idx, (row, column)) in path.iter().enumerate() {
+        let here = forest.display(*row, *column);
+        if idx > 0 && forest.boundary == Boundary::Torus {
+            let (last_row, last_column) = path[idx - 1];
+            let there = forest.display(last_row, last_column);
+            if (here.0 - there.0, here.1 - there.1) != (row - last_row, column - last_column) {
+                segments.push(vec![]);
+            }
+        }
+        segments.last_mut().unwrap().push(here);
+    }
+    segments
+}
+
+// Same layout as the ASCII render: the map repeated as far as the paths reach,
+// one square per cell. Each path is a line through the squares it visits in its
+// own color, and anything it hit is outlined in red.
+pub fn svg(forest: &Forest, paths: &[Vec<(isize, isize)>]) -> String {
+    let (first_column, last_column) = column_span(forest, paths);
+    let columns = (last_column - first_column + 1) as usize;
+    let x = |column: isize| (column - first_column) as usize * CELL;
+    let y = |row: isize| row as usize * CELL;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+        columns * CELL,
+        forest.height() * CELL
+    );
+    svg.push_str(&format!(
+        "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+        hex_color(OPEN)
+    ));
+    for row in 0..forest.height() as isize {
+        for column in first_column..=last_column {
+            if forest.is_hit(row, column) {
+                svg.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                    x(column),
+                    y(row),
+                    CELL,
+                    CELL,
+                    hex_color(TREE)
+                ));
+            }
+        }
+    }
+
+    let center = CELL / 2;
+    for (idx, path) in paths.iter().enumerate() {
+        let color = hex_color(PATH_COLORS[idx % PATH_COLORS.len()]);
+        for segment in segments(forest, path) {
+            let points = segment
+                .iter()
+                .map(|(row, column)| format!("{},{}", x(*column) + center, y(*row) + center))
+                .collect::<Vec<_>>()
+                .join(" ");
+            svg.push_str(&format!(
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+                points, color
+            ));
+        }
+        for (row, column) in segments(forest, path).iter().flatten() {
+            if forest.is_hit(*row, *column) {
+                svg.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+                    x(*column) + 1,
+                    y(*row) + 1,
+                    CELL - 2,
+                    CELL - 2,
+                    color,
+                    hex_color(COLLISION)
+                ));
+            } else {
+                svg.push_str(&format!(
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>\n",
+                    x(*column) + center,
+                    y(*row) + center,
+                    CELL / 4,
+                    color
+                ));
+            }
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// A raster version of the SVG. Visited squares are filled with their path's
+// color and squares that were hit get a red border.
+pub fn png(forest: &Forest, paths: &[Vec<(isize, isize)>]) -> Result<Vec<u8>, String> {
+    let (first_column, last_column) = column_span(forest, paths);
+    let columns = (last_column - first_column + 1) as usize;
+    let width = columns * CELL;
+    let height = forest.height() * CELL;
+    let mut pixels = vec![0; width * height * 3];
+
+    let mut fill = |row: usize, column: usize, inset: usize, color: [u8; 3]| {
+        for y in row * CELL + inset..(row + 1) * CELL - inset {
+            for x in column * CELL + inset..(column + 1) * CELL - inset {
+                let idx = (y * width + x) * 3;
+                pixels[idx..idx + 3].copy_from_slice(&color);
+            }
+        }
+    };
+
+    for row in 0..forest.height() {
+        for column in 0..columns {
+            let hit = forest.is_hit(row as isize, first_column + column as isize);
+            fill(row, column, 0, if hit { TREE } else { OPEN });
+        }
+    }
+    let mut collisions = HashSet::new();
+    for (idx, path) in paths.iter().enumerate() {
+        let color = PATH_COLORS[idx % PATH_COLORS.len()];
+        for (row, column) in path.iter() {
+            let (row, column) = forest.display(*row, *column);
+            if forest.is_hit(row, column) {
+                collisions.insert((row, column));
+            }
+            fill(row as usize, (column - first_column) as usize, 1, color);
+        }
+    }
+    // Borders go on last so a later path can't paint over an earlier collision.
+    for (row, column) in collisions {
+        let (row, column) = (row as usize, (column - first_column) as usize);
+        let inner = (
+            row * CELL + 2..(row + 1) * CELL - 2,
+            column * CELL + 2..(column + 1) * CELL - 2,
+        );
+        for y in row * CELL..(row + 1) * CELL {
+            for x in column * CELL..(column + 1) * CELL {
+                if !(inner.0.contains(&y) && inner.1.contains(&x)) {
+                    let idx = (y * width + x) * 3;
+                    pixels[idx..idx + 3].copy_from_slice(&COLLISION);
+                }
+            }
+        }
+    }
+
+    let mut bytes = vec![];
+    let mut encoder = png::Encoder::new(&mut bytes, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer
+        .write_image_data(&pixels)
+        .map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+#[test]
+fn svg_marks_every_collision() -> Result<(), String> {
+    let forest = Forest::parse(super::TEST_INPUT)?;
+    let path = forest.path(&super::Heading::new(3, 1)?).collect::<Vec<_>>();
+    let svg = svg(&forest, &[path]);
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(svg.matches("stroke=\"#e01010\"").count(), 7);
+    assert_eq!(svg.matches("<circle").count(), 3);
+    Ok(())
+}
+
+#[test]
+fn torus_paths_are_split_where_they_wrap() -> Result<(), String> {
+    let forest = Forest::parse("...\n...\n...\n...")?;
+    let heading = super::Heading::new(2, 1)?;
+    let path = forest.path(&heading).collect::<Vec<_>>();
+    assert_eq!(segments(&forest, &path), vec![path.clone()]);
+
+    let forest = forest.with_boundary(Boundary::Torus);
+    let path = forest.path(&heading).collect::<Vec<_>>();
+    assert_eq!(
+        segments(&forest, &path),
+        vec![
+            vec![(1, 2)],
+            vec![(2, 1)],
+            vec![(3, 0)],
+            vec![(0, 2)],
+            vec![(1, 1)],
+            vec![(2, 0), (3, 2)],
+            vec![(0, 1)],
+            vec![(1, 0), (2, 2)],
+            vec![(3, 1)],
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn png_has_the_right_size() -> Result<(), String> {
+    let forest = Forest::parse(super::TEST_INPUT)?;
+    let path = forest.path(&super::Heading::new(3, 1)?).collect::<Vec<_>>();
+    let bytes = png(&forest, &[path])?;
+    let decoder = png::Decoder::new(bytes.as_slice());
+    let reader = decoder.read_info().map_err(|e| e.to_string())?;
+    assert_eq!(reader.info().width as usize, 31 * CELL);
+    assert_eq!(reader.info().height as usize, 11 * CELL);
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use super::Forest;
+
+const COLORS: [u8; 6] = [31, 32, 33, 34, 35, 36];
+
+// The leftmost and rightmost columns to draw so that the whole map and every
+// path fit.
+pub fn column_span(forest: &Forest, paths: &[Vec<(isize, isize)>]) -> (isize, isize) {
+    let columns = paths
+        .iter()
+        .flatten()
+        .map(|(row, column)| forest.display(*row, *column).1);
+    let first_column = columns.clone().min().unwrap_or(0).min(0);
+    let last_column = columns.max().unwrap_or(0).max(forest.width() as isize - 1);
+    (first_column, last_column)
+}
+
+// Draws the forest repeated as far to the left and right as any of the paths
+// reach, marking visited squares that cost nothing with `O` and anything that
+// was hit with `X`. With `color` each path gets its own ANSI color, and later paths are
+// drawn over earlier ones.
+pub fn render(forest: &Forest, paths: &[Vec<(isize, isize)>], color: bool) -> String {
+    let (first_column, last_column) = column_span(forest, paths);
+    let mut marks = vec![vec![None; (last_column - first_column + 1) as usize]; forest.height()];
+    for (idx, path) in paths.iter().enumerate() {
+        for (row, column) in path.iter() {
+            let (row, column) = forest.display(*row, *column);
+            marks[row as usize][(column - first_column) as usize] = Some(idx);
+        }
+    }
+
+    let mut rendered = String::new();
+    for (row, marks) in marks.iter().enumerate() {
+        for (offset, mark) in marks.iter().enumerate() {
+            let column = first_column + offset as isize;
+            let hit = forest.is_hit(row as isize, column);
+            match (mark, color) {
+                (None, _) => rendered.push(forest.symbol(row as isize, column)),
+                (Some(_), false) => rendered.push(if hit { 'X' } else { 'O' }),
+                (Some(idx), true) => {
+                    let color = COLORS[idx % COLORS.len()];
+                    if hit {
+                        rendered.push_str(&format!("\x1b[1;{}mX\x1b[0m", color));
+                    } else {
+                        rendered.push_str(&format!("\x1b[{}mO\x1b[0m", color));
+                    }
+                }
+            }
+        }
+        rendered.push('\n');
+    }
+    rendered
+}
+
+#[test]
+fn render_test_input() -> Result<(), String> {
+    let forest = Forest::parse(super::TEST_INPUT)?;
+    let path = forest.path(&super::Heading::new(3, 1)?).collect::<Vec<_>>();
+    let rendered = render(&forest, &[path], false);
+    let expected = "\
+..##.........##.........##.....
+#..O#...#..#...#...#..#...#...#
+.#....X..#..#....#..#..#....#..
+..#.#...#O#..#.#...#.#..#.#...#
+.#...##..#..X...##..#..#...##..
+..#.##.......#.X#.......#.##...
+.#.#.#....#.#.#.#.O..#.#.#.#...
+.#........#.#........X.#.......
+#.##...#...#.##...#...#.X#...#.
+#...##....##...##....##...#X...
+.#..#...#.#.#..#...#.#.#..#...X
+";
+    assert_eq!(rendered, expected);
+    Ok(())
+}
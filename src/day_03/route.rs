@@ -0,0 +1,143 @@
+use std::{cmp::Reverse, collections::BinaryHeap, ops::RangeInclusive};
+
+use super::{terrain::Tally, Forest};
+
+// right, down and down-right
+pub const DEFAULT_STEPS: [(isize, isize); 3] = [(1, 0), (0, 1), (1, 1)];
+
+// The route keeps a cost and a way back for every square in the window, so
+// the window can't be too big to hold those.
+const MAX_SQUARES: usize = 10_000_000;
+
+pub struct Route {
+    pub squares: Vec<(isize, isize)>,
+    pub tally: Tally,
+}
+
+// Steps are written `right,down` like slopes, but unlike slopes they're
+// allowed to stay on the same row.
+pub fn parse_step(s: &str) -> Result<(isize, isize), String> {
+    let bad_step = || format!("Steps look like 1,0 buddy, not {}", s);
+    let (horizontal, vertical) = s.split_once(',').ok_or_else(bad_step)?;
+    let step = (
+        horizontal.trim().parse().map_err(|_| bad_step())?,
+        vertical.trim().parse().map_err(|_| bad_step())?,
+    );
+    if step == (0, 0) {
+        return Err(String::from("A step of 0,0 doesn't go anywhere, buddy."));
+    }
+    Ok(step)
+}
+
+// Dijkstra from the top left square to anywhere on the bottom row, staying
+// inside the given columns of the (repeating) map. Every square entered costs
+// whatever its terrain costs, and ties go to the route with fewer steps. With
+// `avoid` any square that costs something is off limits entirely.
+pub fn cheapest_route(
+    forest: &Forest,
+    steps: &[(isize, isize)],
+    window: RangeInclusive<isize>,
+    avoid: bool,
+) -> Result<Route, String> {
+    if !window.contains(&0) {
+        return Err(String::from("The window has to include column 0."));
+    }
+    let height = forest.height() as isize;
+    let squares = window
+        .end()
+        .checked_sub(*window.start())
+        .and_then(|width| width.checked_add(1))
+        .and_then(|width| (width as usize).checked_mul(forest.height()))
+        .filter(|squares| *squares <= MAX_SQUARES)
+        .ok_or_else(|| {
+            format!(
+                "The window {:?} is too wide to route through, buddy. It can cover at most {} squares.",
+                window, MAX_SQUARES
+            )
+        })?;
+    let width = window.end() - window.start() + 1;
+    let idx = |row: isize, column: isize| (row * width + column - window.start()) as usize;
+
+    let mut best = vec![None; squares];
+    let mut previous = vec![None; squares];
+    let mut queue = BinaryHeap::new();
+    best[idx(0, 0)] = Some((0, 0));
+    queue.push(Reverse((0, 0, 0, 0)));
+
+    while let Some(Reverse((cost, count, row, column))) = queue.pop() {
+        if best[idx(row, column)] != Some((cost, count)) {
+            continue;
+        }
+        if row == height - 1 {
+            let mut squares = vec![];
+            let mut here = (row, column);
+            while here != (0, 0) {
+                squares.push(here);
+                here = previous[idx(here.0, here.1)].unwrap();
+            }
+            squares.reverse();
+            let mut tally = Tally::new(&forest.legend);
+            for (row, column) in squares.iter() {
+                tally.add(&forest.legend, forest.terrain(*row, *column));
+            }
+            return Ok(Route { squares, tally });
+        }
+        for (horizontal, vertical) in steps.iter() {
+            let (next_row, next_column) =
+                match (row.checked_add(*vertical), column.checked_add(*horizontal)) {
+                    (Some(row), Some(column)) => (row, column),
+                    _ => continue,
+                };
+            if !(0..height).contains(&next_row) || !window.contains(&next_column) {
+                continue;
+            }
+            if avoid && forest.is_hit(next_row, next_column) {
+                continue;
+            }
+            let terrain = forest.terrain(next_row, next_column);
+            let next = (cost + forest.legend.terrains[terrain].cost, count + 1);
+            match best[idx(next_row, next_column)] {
+                Some(known) if known <= next => {}
+                _ => {
+                    best[idx(next_row, next_column)] = Some(next);
+                    previous[idx(next_row, next_column)] = Some((row, column));
+                    queue.push(Reverse((next.0, next.1, next_row, next_column)));
+                }
+            }
+        }
+    }
+    Err(String::from("There's no way down from here, buddy."))
+}
+
+#[test]
+fn safe_route_through_test_input() -> Result<(), String> {
+    let forest = Forest::parse(super::TEST_INPUT)?;
+    let route = cheapest_route(&forest, &DEFAULT_STEPS, 0..=10, true)?;
+    assert_eq!(route.tally.cost, 0);
+    assert_eq!(route.squares.last().unwrap().0, 10);
+    let mut here = (0, 0);
+    for square in route.squares.iter() {
+        assert!(!forest.is_hit(square.0, square.1));
+        let step = (square.1 - here.1, square.0 - here.0);
+        assert!(DEFAULT_STEPS.contains(&step));
+        here = *square;
+    }
+    Ok(())
+}
+
+#[test]
+fn cheapest_route_when_trees_cant_be_avoided() -> Result<(), String> {
+    let forest = Forest::parse("..#\n###\n..#")?;
+    assert!(cheapest_route(&forest, &[(0, 1)], 0..=2, true).is_err());
+    let route = cheapest_route(&forest, &[(0, 1), (1, 0)], 0..=2, false)?;
+    assert_eq!(route.squares, vec![(1, 0), (2, 0)]);
+    assert_eq!(route.tally.cost, 1);
+    assert!(cheapest_route(&forest, &DEFAULT_STEPS, 1..=2, false).is_err());
+    assert!(cheapest_route(&forest, &DEFAULT_STEPS, 0..=isize::MAX, false).is_err());
+    assert!(cheapest_route(&forest, &DEFAULT_STEPS, isize::MIN..=0, false).is_err());
+    assert!(cheapest_route(&forest, &DEFAULT_STEPS, 0..=100_000_000_000, false).is_err());
+    assert!(cheapest_route(&forest, &[(isize::MAX, 0), (0, isize::MAX)], 0..=2, false).is_err());
+    assert_eq!(parse_step("1,0")?, (1, 0));
+    assert!(parse_step("0,0").is_err());
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::{fs, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Terrain {
+    pub symbol: char,
+    pub name: String,
+    pub cost: usize,
+}
+
+impl FromStr for Terrain {
+    type Err = String;
+
+    // Terrain is written `symbol=name:cost`, e.g. `^=rock:5`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad_terrain = || format!("Terrain looks like ^=rock:5 buddy, not {}", s);
+        let mut chars = s.chars();
+        let symbol = chars.next().ok_or_else(bad_terrain)?;
+        let rest = chars.as_str().strip_prefix('=').ok_or_else(bad_terrain)?;
+        let (name, cost) = rest.split_once(':').ok_or_else(bad_terrain)?;
+        if name.is_empty() {
+            return Err(bad_terrain());
+        }
+        Ok(Self {
+            symbol,
+            name: String::from(name),
+            cost: cost.trim().parse().map_err(|_| bad_terrain())?,
+        })
+    }
+}
+
+// The kinds of terrain a map can contain.
+#[derive(Debug, Clone)]
+pub struct Legend {
+    pub terrains: Vec<Terrain>,
+}
+
+impl Default for Legend {
+    fn default() -> Self {
+        Self {
+            terrains: vec![
+                Terrain {
+                    symbol: '.',
+                    name: String::from("open"),
+                    cost: 0,
+                },
+                Terrain {
+                    symbol: '#',
+                    name: String::from("tree"),
+                    cost: 1,
+                },
+            ],
+        }
+    }
+}
+
+impl Legend {
+    // Adds a terrain, replacing any existing terrain with the same symbol.
+    pub fn insert(&mut self, terrain: Terrain) {
+        match self
+            .terrains
+            .iter_mut()
+            .find(|t| t.symbol == terrain.symbol)
+        {
+            Some(existing) => *existing = terrain,
+            None => self.terrains.push(terrain),
+        }
+    }
+
+    // One terrain per line, blank lines are skipped.
+    pub fn extend_from_file(&mut self, path: &str) -> Result<(), String> {
+        let contents =
+            fs::read_to_string(path).map_err(|_| format!("bad legend path, buddy: {}", path))?;
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            self.insert(line.trim().parse()?);
+        }
+        Ok(())
+    }
+
+    pub fn index(&self, symbol: char) -> Option<usize> {
+        self.terrains.iter().position(|t| t.symbol == symbol)
+    }
+}
+
+// How many squares of each terrain a trip landed on, indexed like the legend,
+// and what it cost all together.
+#[derive(Debug, PartialEq)]
+pub struct Tally {
+    pub counts: Vec<usize>,
+    pub cost: usize,
+}
+
+impl Tally {
+    pub fn new(legend: &Legend) -> Self {
+        Self {
+            counts: vec![0; legend.terrains.len()],
+            cost: 0,
+        }
+    }
+
+    pub fn add(&mut self, legend: &Legend, terrain: usize) {
+        self.counts[terrain] += 1;
+        self.cost += legend.terrains[terrain].cost;
+    }
+
+    pub fn describe(&self, legend: &Legend) -> String {
+        let counts = legend
+            .terrains
+            .iter()
+            .zip(self.counts.iter())
+            .filter(|(_, count)| **count > 0)
+            .map(|(terrain, count)| format!("{} {}", terrain.name, count))
+            .collect::<Vec<_>>();
+        format!("cost {} ({})", self.cost, counts.join(", "))
+    }
+}
+
+#[test]
+fn parse_terrain() -> Result<(), String> {
+    assert_eq!(
+        "^=rock:5".parse::<Terrain>()?,
+        Terrain {
+            symbol: '^',
+            name: String::from("rock"),
+            cost: 5
+        }
+    );
+    assert!("^rock:5".parse::<Terrain>().is_err());
+    assert!("^=rock".parse::<Terrain>().is_err());
+    assert!("^=:5".parse::<Terrain>().is_err());
+
+    let mut legend = Legend::default();
+    legend.insert("#=tree:3".parse()?);
+    legend.insert("~=ice:0".parse()?);
+    assert_eq!(legend.terrains.len(), 3);
+    assert_eq!(
+        legend.index('#').map(|idx| legend.terrains[idx].cost),
+        Some(3)
+    );
+    assert_eq!(legend.index('?'), None);
+    Ok(())
+}
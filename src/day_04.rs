@@ -1,19 +1,29 @@
-use std::{cell::RefCell, fs, rc::Rc};
+use std::{convert::TryFrom, fs, io, ops::Range, path::Path};
+
+use crate::cli::Flags;
 
 use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    bytes::complete::take_while_m_n,
-    character::complete::alphanumeric1,
-    character::complete::{digit1, multispace0},
-    combinator::map,
-    combinator::{all_consuming, map_res},
-    multi::fold_many0,
-    multi::fold_many1,
-    sequence::tuple,
-    IResult,
+    bytes::complete::{tag, take_till1},
+    character::complete::{alphanumeric1, multispace0},
+    combinator::{consumed, map},
+    multi::many0,
+    sequence::{preceded, separated_pair, terminated},
+    IResult, Offset,
 };
 
+mod convert;
+mod generate;
+mod height;
+mod repl;
+mod report;
+mod rules;
+mod suggest;
+mod validated;
+
+use report::Report;
+use rules::Rules;
+use validated::ValidPassport;
+
 #[derive(Debug, Clone)]
 struct Passport {
     original: String,
@@ -25,6 +35,9 @@ struct Passport {
     ecl: Option<String>,
     pid: Option<String>,
     cid: Option<String>,
+    extras: Vec<(String, String)>,
+    // Keys the record gave more than once. The last value given wins.
+    duplicates: Vec<String>,
 }
 
 impl Passport {
@@ -39,259 +52,224 @@ impl Passport {
             ecl: None,
             pid: None,
             cid: None,
+            extras: vec![],
+            duplicates: vec![],
         }
     }
-    fn is_valid(&self) -> bool {
-        [
-            &self.byr, &self.iyr, &self.eyr, &self.hgt, &self.hcl, &self.ecl, &self.pid,
-        ]
-        .iter()
-        .all(|a| a.is_some())
-    }
-    fn is_valid_2(&self) -> bool {
-        let temparray = [
-            (self.byr.as_ref()).map(|byr: &String| {
-                let parsed = byr.parse::<usize>().ok()?;
-                if parsed >= 1920 && parsed <= 2002 {
-                    Some(true)
-                } else {
-                    None
-                }
-            }),
-            (self.iyr.as_ref()).map(|byr: &String| {
-                let parsed = byr.parse::<usize>().ok()?;
-                if parsed >= 2010 && parsed <= 2020 {
-                    Some(true)
-                } else {
-                    None
-                }
-            }),
-            (self.eyr.as_ref()).map(|byr: &String| {
-                let parsed = byr.parse::<usize>().ok()?;
-                if parsed >= 2020 && parsed <= 2030 {
-                    Some(true)
-                } else {
-                    None
-                }
-            }),
-            (self.hgt.as_deref()).map(|byr: &str| {
-                let (_, h) = all_consuming(height)(byr).ok()?;
-                if h.in_bounds() {
-                    Some(true)
-                } else {
-                    None
-                }
-            }),
-            (self.hcl.as_deref()).map(|byr: &str| {
-                let (_, _) = hex(byr).ok()?;
-                Some(true)
-            }),
-            (self.ecl.as_deref()).map(|byr: &str| {
-                let (_, _) = eye_color(byr).ok()?;
-                Some(true)
-            }),
-            (self.pid.as_deref()).map(|byr: &str| {
-                if byr.len() == 9 && byr.parse::<u64>().is_ok() {
-                    Some(true)
-                } else {
-                    None
+
+    fn from_fields(original: &str, fields: &[Field]) -> Self {
+        let mut passport = Self::empty(original);
+        for (idx, field) in fields.iter().enumerate() {
+            let seen = fields[..idx].iter().any(|earlier| earlier.key == field.key);
+            if seen && !passport.duplicates.iter().any(|key| key == field.key) {
+                passport.duplicates.push(String::from(field.key));
+            }
+            let value = Some(String::from(field.value));
+            match field.key {
+                "byr" => passport.byr = value,
+                "iyr" => passport.iyr = value,
+                "eyr" => passport.eyr = value,
+                "hgt" => passport.hgt = value,
+                "hcl" => passport.hcl = value,
+                "ecl" => passport.ecl = value,
+                "pid" => passport.pid = value,
+                "cid" => passport.cid = value,
+                _ if seen => {
+                    let extra = passport.extras.iter_mut().find(|(key, _)| key == field.key);
+                    extra.unwrap().1 = String::from(field.value);
                 }
-            }),
-        ];
-        temparray.iter().map(|a| a.unwrap()).all(|a| a.is_some())
+                _ => passport
+                    .extras
+                    .push((String::from(field.key), String::from(field.value))),
+            }
+        }
+        passport
     }
-}
 
-#[derive(Debug)]
-enum Unit {
-    Centimeter,
-    Inch,
-}
-
-impl Unit {
-    fn from(s: &str) -> Result<Unit, String> {
-        Ok(match s {
-            "cm" => Unit::Centimeter,
-            "in" => Unit::Inch,
-            _ => return Err(String::from("Not a valid unit")),
-        })
+    // Only the puzzle's rules, as typed fields. Rules profiles can differ.
+    fn is_valid_2(&self) -> bool {
+        ValidPassport::try_from(self).is_ok()
     }
-}
 
-fn eye_color(input: &str) -> IResult<&str, &str> {
-    alt((
-        tag("amb"),
-        tag("blu"),
-        tag("brn"),
-        tag("gry"),
-        tag("grn"),
-        tag("hzl"),
-        tag("oth"),
-    ))(input)
-}
-
-#[derive(Debug)]
-struct Height {
-    value: usize,
-    unit: Unit,
-}
-
-impl Height {
-    fn in_bounds(&self) -> bool {
-        match self.unit {
-            Unit::Centimeter => self.value >= 150 && self.value <= 193,
-            Unit::Inch => self.value >= 59 && self.value <= 76,
+    fn get(&self, key: &str) -> Option<&str> {
+        match key {
+            "byr" => &self.byr,
+            "iyr" => &self.iyr,
+            "eyr" => &self.eyr,
+            "hgt" => &self.hgt,
+            "hcl" => &self.hcl,
+            "ecl" => &self.ecl,
+            "pid" => &self.pid,
+            "cid" => &self.cid,
+            _ => {
+                return self
+                    .extras
+                    .iter()
+                    .find(|(extra, _)| extra == key)
+                    .map(|(_, value)| value.as_str())
+            }
         }
+        .as_deref()
     }
 }
 
-fn height(input: &str) -> IResult<&str, Height> {
-    map(
-        tuple((
-            map_res(digit1, |a: &str| a.parse::<usize>()),
-            map_res(alt((tag("cm"), tag("in"))), Unit::from),
-        )),
-        |(value, unit)| Height { value, unit },
-    )(input)
-}
-
-fn is_hex_digit(c: char) -> bool {
-    c.is_ascii_hexdigit() && !c.is_ascii_uppercase()
-}
-
-fn hex(input: &str) -> IResult<&str, (&str, &str)> {
-    all_consuming(tuple((tag("#"), (take_while_m_n)(6, 6, is_hex_digit))))(input)
-}
-
-#[test]
-fn hex_works() {
-    let (_, actual) = hex("#abcdef").unwrap();
-    assert_eq!(actual, ("#", "abcdef"));
-}
-
-type PassportBuild = Rc<RefCell<Passport>>;
-
-fn byr(passport: PassportBuild, input: &str) -> IResult<&str, PassportBuild> {
-    map(tuple((tag("byr:"), alphanumeric1)), |(_, byr)| {
-        passport.borrow_mut().byr = Some(String::from(byr));
-        passport.clone()
-    })(input)
+#[derive(Debug, PartialEq)]
+struct Field<'a> {
+    key: &'a str,
+    value: &'a str,
+    // Where the whole `key:value` sits in the record, in bytes.
+    span: Range<usize>,
 }
 
-fn iyr(passport: PassportBuild, input: &str) -> IResult<&str, PassportBuild> {
-    map(tuple((tag("iyr:"), alphanumeric1)), |(_, iyr)| {
-        passport.borrow_mut().iyr = Some(String::from(iyr));
-        passport.clone()
-    })(input)
+// Anything up to the next whitespace. Whether it makes sense is up to the rules.
+fn value(input: &str) -> IResult<&str, &str> {
+    take_till1(char::is_whitespace)(input)
 }
 
-fn eyr(passport: PassportBuild, input: &str) -> IResult<&str, PassportBuild> {
-    map(tuple((tag("eyr:"), alphanumeric1)), |(_, eyr)| {
-        passport.borrow_mut().eyr = Some(String::from(eyr));
-        passport.clone()
-    })(input)
+fn key_value(input: &str) -> IResult<&str, (&str, &str)> {
+    separated_pair(alphanumeric1, tag(":"), value)(input)
 }
 
-fn hgt(passport: PassportBuild, input: &str) -> IResult<&str, PassportBuild> {
-    map(tuple((tag("hgt:"), alphanumeric1)), |(_, hgt)| {
-        passport.borrow_mut().hgt = Some(String::from(hgt));
-        passport.clone()
-    })(input)
+fn acceptable_whitespace(input: &str) -> IResult<&str, ()> {
+    map(multispace0, |_| ())(input)
 }
 
-fn hcl(passport: PassportBuild, input: &str) -> IResult<&str, PassportBuild> {
-    map(
-        tuple((
-            tag("hcl:"),
-            fold_many1(alt((alphanumeric1, tag("#"))), String::new(), |mut a, b| {
-                a.push_str(b);
-                a
-            }),
-        )),
-        |(_, hcl)| {
-            passport.borrow_mut().hcl = Some(String::from(hcl));
-            passport.clone()
-        },
-    )(input)
+// Anything left over after the last field that parsed is an error, pointing at
+// the byte offset in the record where parsing stopped.
+fn fields(record: &str) -> Result<Vec<Field<'_>>, String> {
+    let (rest, fields) = preceded(
+        acceptable_whitespace,
+        many0(terminated(consumed(key_value), acceptable_whitespace)),
+    )(record)
+    .map_err(|e| format!("{:?}", e))?;
+    if !rest.is_empty() {
+        let unparsed = rest.split_whitespace().next().unwrap_or(rest);
+        return Err(format!(
+            "couldn't parse {:?} at offset {}",
+            unparsed,
+            record.offset(rest)
+        ));
+    }
+    Ok(fields
+        .into_iter()
+        .map(|(whole, (key, value))| {
+            let start = record.offset(whole);
+            Field {
+                key,
+                value,
+                span: start..start + whole.len(),
+            }
+        })
+        .collect())
 }
 
-fn ecl(passport: PassportBuild, input: &str) -> IResult<&str, PassportBuild> {
-    map(
-        tuple((
-            tag("ecl:"),
-            fold_many1(alt((alphanumeric1, tag("#"))), String::new(), |mut a, b| {
-                a.push_str(b);
-                a
-            }),
-        )),
-        |(_, ecl)| {
-            passport.borrow_mut().ecl = Some(String::from(ecl));
-            passport.clone()
-        },
-    )(input)
+// A record is a run of non-blank lines.
+#[derive(Debug, PartialEq)]
+struct Record<'a> {
+    // Both count from 1.
+    index: usize,
+    line: usize,
+    text: &'a str,
 }
 
-fn pid(passport: PassportBuild, input: &str) -> IResult<&str, PassportBuild> {
-    map(
-        tuple((
-            tag("pid:"),
-            fold_many1(alt((alphanumeric1, tag("#"))), String::new(), |mut a, b| {
-                a.push_str(b);
-                a
+fn records(input: &str) -> Vec<Record<'_>> {
+    let mut records: Vec<Record> = vec![];
+    let mut in_record = false;
+    for (idx, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            in_record = false;
+            continue;
+        }
+        let end = input.offset(line) + line.len();
+        match records.last_mut() {
+            Some(record) if in_record => {
+                let start = input.offset(record.text);
+                record.text = &input[start..end];
+            }
+            _ => records.push(Record {
+                index: records.len() + 1,
+                line: idx + 1,
+                text: &input[input.offset(line)..end],
             }),
-        )),
-        |(_, pid)| {
-            passport.borrow_mut().pid = Some(String::from(pid));
-            passport.clone()
-        },
-    )(input)
+        }
+        in_record = true;
+    }
+    records
 }
 
-fn cid(passport: PassportBuild, input: &str) -> IResult<&str, PassportBuild> {
-    map(tuple((tag("cid:"), alphanumeric1)), |(_, cid)| {
-        passport.borrow_mut().cid = Some(String::from(cid));
-        passport.clone()
-    })(input)
+fn passport(input: &str) -> Result<Passport, String> {
+    Ok(Passport::from_fields(input, &fields(input)?))
 }
 
-fn other(passport: PassportBuild, input: &str) -> IResult<&str, PassportBuild> {
-    map(
-        tuple((alphanumeric1, tag(":"), alphanumeric1)),
-        |(_, _, _)| passport.clone(),
-    )(input)
+#[test]
+fn parse_fields() -> Result<(), String> {
+    let record = "ecl:gry pid:860033327\nhcl:#fffffd zzz:1";
+    let fields = fields(record)?;
+    assert_eq!(
+        fields.iter().map(|f| (f.key, f.value)).collect::<Vec<_>>(),
+        vec![
+            ("ecl", "gry"),
+            ("pid", "860033327"),
+            ("hcl", "#fffffd"),
+            ("zzz", "1")
+        ]
+    );
+    assert_eq!(&record[fields[2].span.clone()], "hcl:#fffffd");
+
+    let passport = Passport::from_fields(record, &fields);
+    assert_eq!(passport.hcl.as_deref(), Some("#fffffd"));
+    assert_eq!(passport.byr, None);
+    assert_eq!(
+        passport.extras,
+        vec![(String::from("zzz"), String::from("1"))]
+    );
+
+    let repeated = self::passport("zzz:1 pid:1 zzz:2 pid:2 pid:3")?;
+    assert_eq!(repeated.pid.as_deref(), Some("3"));
+    assert_eq!(
+        repeated.extras,
+        vec![(String::from("zzz"), String::from("2"))]
+    );
+    assert_eq!(repeated.duplicates, vec!["zzz", "pid"]);
+    Ok(())
 }
 
-fn acceptable_whitespace(input: &str) -> IResult<&str, ()> {
-    map(multispace0, |_| ())(input)
+#[test]
+fn values_run_to_whitespace() -> Result<(), String> {
+    let record = "ecl:blu-grn\thgt:5'10\"\r\npid:#1:2 \r\n";
+    assert_eq!(
+        fields(record)?
+            .iter()
+            .map(|f| (f.key, f.value))
+            .collect::<Vec<_>>(),
+        vec![("ecl", "blu-grn"), ("hgt", "5'10\""), ("pid", "#1:2")]
+    );
+
+    let input = "ecl:gry\r\nhgt:5'10\"\r\n\r\n\tpid:1\r\n";
+    assert_eq!(
+        records(input)
+            .iter()
+            .map(|record| (record.line, record.text))
+            .collect::<Vec<_>>(),
+        vec![(1, "ecl:gry\r\nhgt:5'10\""), (4, "\tpid:1")]
+    );
+    Ok(())
 }
 
-fn passport<'a>(input: &'a str) -> Result<Passport, String> {
-    let pp = Rc::new(RefCell::new(Passport::empty(&input)));
-    fold_many0(
-        map(
-            tuple((
-                alt((
-                    |input: &'a str| byr(pp.clone(), &input),
-                    |input: &'a str| iyr(pp.clone(), &input),
-                    |input: &'a str| eyr(pp.clone(), &input),
-                    |input: &'a str| hgt(pp.clone(), &input),
-                    |input: &'a str| hcl(pp.clone(), &input),
-                    |input: &'a str| ecl(pp.clone(), &input),
-                    |input: &'a str| pid(pp.clone(), &input),
-                    |input: &'a str| cid(pp.clone(), &input),
-                    |input: &'a str| other(pp.clone(), &input),
-                )),
-                acceptable_whitespace,
-            )),
-            |(pp, _)| pp,
-        ),
-        pp.clone(),
-        |a, _| a,
-    )(input)
-    .map_err(|e| format!("{:?}", e))?;
-    Ok(Rc::try_unwrap(pp)
-        .map_err(|_| String::from("Could not unwrap Rc"))?
-        .into_inner())
+#[test]
+fn leftovers_are_reported() {
+    assert_eq!(
+        fields("ecl:gry hgt 5'10\" pid:860033327").unwrap_err(),
+        "couldn't parse \"hgt\" at offset 8"
+    );
+    assert_eq!(
+        fields("ecl:gry hgt: pid:860033327").unwrap_err(),
+        "couldn't parse \"hgt:\" at offset 8"
+    );
+    assert_eq!(
+        fields("ecl:gry\n!!").unwrap_err(),
+        "couldn't parse \"!!\" at offset 8"
+    );
 }
 
 #[test]
@@ -311,7 +289,7 @@ eyr:2022
 iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719\
 ";
 
-    let count = part2_solution(&input)?;
+    let count = part2_solution(input, &Rules::default_profile()?)?;
     assert_eq!(4, count);
     Ok(())
 }
@@ -333,7 +311,7 @@ hgt:59cm ecl:zzz
 eyr:2038 hcl:74454a iyr:2023
 pid:3556412378 byr:2007\
 ";
-    let count = part2_solution(&input)?;
+    let count = part2_solution(input, &Rules::default_profile()?)?;
     assert_eq!(0, count);
     Ok(())
 }
@@ -356,61 +334,178 @@ hcl:#cfa07d eyr:2025 pid:166559648
 iyr:2011 ecl:brn hgt:59in\
 ";
 
-    let count = part1_solution(&input)?;
+    let count = part1_solution(input, &Rules::default_profile()?)?;
     assert_eq!(count, 2);
 
     Ok(())
 }
 
-fn part1_solution(input: &str) -> Result<usize, String> {
-    Ok(input
-        .trim()
-        .split("\n\n")
-        .map(|entry: &str| {
-            passport(entry).map_or_else(
-                |e| {
-                    println!("Invalid pp {:?}", e);
-                    0
-                },
-                |a| if a.is_valid() { 1 } else { 0 },
-            )
-        })
-        .fold(0, |a, b| a + b))
+fn part1_solution(input: &str, rules: &Rules) -> Result<usize, String> {
+    Ok(Report::new(input, rules).count(|entry| entry.lax))
 }
 
 pub fn part1() -> Result<(), String> {
-    let input =
-        fs::read_to_string("src/day_04_input.txt").map_err(|_| String::from("bad path, buddy."))?;
-    let count = part1_solution(&input.trim())?;
+    part1_with(&Flags::parse(&[])?)
+}
+
+fn part1_with(flags: &Flags) -> Result<(), String> {
+    let input = read_passports(flags)?;
+    let count = part1_solution(&input, &rules_from_flags(flags)?)?;
     println!("Part1: {}", count);
     Ok(())
 }
 
-fn part2_solution(input: &str) -> Result<usize, String> {
-    Ok(input
-        .trim()
-        .split("\n\n")
-        .map(|entry: &str| {
-            passport(entry).map_or_else(
-                |e| {
-                    println!("Invalid pp {:?}", e);
-                    0
-                },
-                |a| {
-                    if a.is_valid() && a.is_valid_2() {
-                        1
-                    } else {
-                        0
-                    }
-                },
-            )
-        })
-        .fold(0, |a, b| a + b))
+#[cfg(test)]
+fn part2_solution(input: &str, rules: &Rules) -> Result<usize, String> {
+    Ok(Report::new(input, rules).count(|entry| entry.strict))
+}
+
+fn read_passports(flags: &Flags) -> Result<String, String> {
+    let path = flags.value("passports")?.unwrap_or("src/day_04_input.txt");
+    fs::read_to_string(path).map_err(|_| format!("bad passports path, buddy: {}", path))
+}
+
+fn rules_from_flags(flags: &Flags) -> Result<Rules, String> {
+    match flags.value("rules")? {
+        Some(path) => Rules::from_file(path),
+        None => Rules::default_profile(),
+    }
+}
+
+fn flags(args: &[String], extra: &[&str]) -> Result<Flags, String> {
+    Flags::parse(args)?.only(&[&["passports", "rules"], extra].concat())
+}
+
+pub fn cli(args: &[String]) -> Result<(), String> {
+    match args.split_first() {
+        None => {
+            part1()?;
+            part2()
+        }
+        Some((command, rest)) => match command.as_str() {
+            "part1" => part1_with(&flags(rest, &[])?),
+            "part2" => part2_with(&flags(rest, &[])?),
+            "report" => report_with(&flags(rest, &["format"])?),
+            "convert" => convert_with(&flags(rest, &["from", "to", "out"])?),
+            "repl" => repl_with(&flags(rest, &["color"])?),
+            "generate" => generate_with(&Flags::parse(rest)?.only(&[
+                "count",
+                "seed",
+                "valid",
+                "missing",
+                "out-of-range",
+                "out",
+            ])?),
+            _ => Err(format!("Unknown day 4 command: {}", command)),
+        },
+    }
 }
+
 pub fn part2() -> Result<(), String> {
-    let input =
-        fs::read_to_string("src/day_04_input.txt").map_err(|_| String::from("bad path, buddy."))?;
-    let count = part2_solution(&input.trim())?;
-    println!("Part2: {} WRONG", count);
+    part2_with(&Flags::parse(&[])?)
+}
+
+fn part2_with(flags: &Flags) -> Result<(), String> {
+    let input = read_passports(flags)?;
+    let report = Report::new(&input, &rules_from_flags(flags)?);
+    println!("Part2: {} WRONG", report.count(|entry| entry.strict));
+    println!("  {}", report.describe_counts());
     Ok(())
 }
+
+// The batch goes to --out, or stdout with the expected counts on stderr so they
+// don't end up in the batch. The counts only hold for the puzzle's rules, which
+// is why there's no --rules here.
+fn generate_with(flags: &Flags) -> Result<(), String> {
+    let number = |name: &str, default: usize| -> Result<usize, String> {
+        match flags.value(name)? {
+            Some(n) => n
+                .parse()
+                .map_err(|_| format!("--{} should be a number, not {}", name, n)),
+            None => Ok(default),
+        }
+    };
+    let default = generate::Mix::default();
+    let mix = generate::Mix {
+        valid: number("valid", default.valid)?,
+        missing: number("missing", default.missing)?,
+        out_of_range: number("out-of-range", default.out_of_range)?,
+    };
+    let batch = generate::generate(number("count", 100)?, mix, number("seed", 0)? as u64)?;
+    let counts = format!(
+        "Expected under the puzzle's rules, part1: {}, part2: {}",
+        batch.lax, batch.strict
+    );
+    match flags.value("out")? {
+        Some(out) => {
+            fs::write(out, &batch.text).map_err(|e| format!("couldn't write {}: {}", out, e))?;
+            println!("{}", counts);
+        }
+        None => {
+            println!("{}", batch);
+            eprintln!("{}", counts);
+        }
+    }
+    Ok(())
+}
+
+fn repl_with(flags: &Flags) -> Result<(), String> {
+    let rules = rules_from_flags(flags)?;
+    let stdin = io::stdin();
+    repl::run(
+        stdin.lock(),
+        &mut io::stdout(),
+        &rules,
+        flags.switch("color"),
+    )
+}
+
+fn report_with(flags: &Flags) -> Result<(), String> {
+    let report = Report::new(&read_passports(flags)?, &rules_from_flags(flags)?);
+    match flags.value("format")?.unwrap_or("text") {
+        "text" => println!("{}", report),
+        "json" => println!("{}", report.to_json()?),
+        format => return Err(format!("--format is text or json, not {}", format)),
+    }
+    Ok(())
+}
+
+// The format comes from --from and --to, or else the file's extension. Anything
+// that isn't .json or .csv is the puzzle's format.
+fn format_of(flags: &Flags, flag: &str, path: Option<&str>) -> Result<String, String> {
+    let format = match flags.value(flag)? {
+        Some(format) => format,
+        None => match path.and_then(|path| Path::new(path).extension()) {
+            Some(ext) if ext == "json" => "json",
+            Some(ext) if ext == "csv" => "csv",
+            _ => "native",
+        },
+    };
+    match format {
+        "native" | "json" | "csv" => Ok(String::from(format)),
+        _ => Err(format!("--{} is native, json or csv, not {}", flag, format)),
+    }
+}
+
+fn convert_with(flags: &Flags) -> Result<(), String> {
+    let path = flags.value("passports")?;
+    let input = read_passports(flags)?;
+    let passports = match format_of(flags, "from", path)?.as_str() {
+        "json" => convert::from_json(&input)?,
+        "csv" => convert::from_csv(&input)?,
+        _ => convert::from_native(&input)?,
+    };
+    let out = flags.value("out")?;
+    let output = match format_of(flags, "to", out)?.as_str() {
+        "json" => convert::to_json(&passports)?,
+        "csv" => convert::to_csv(&passports)?,
+        _ => convert::to_native(&passports),
+    };
+    match out {
+        Some(out) => fs::write(out, output).map_err(|e| format!("couldn't write {}: {}", out, e)),
+        None => {
+            println!("{}", output);
+            Ok(())
+        }
+    }
+}
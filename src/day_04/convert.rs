@@ -0,0 +1,335 @@
+// Moving passport batches between the puzzle's format, JSON and CSV.
+use std::fmt;
+
+use serde::{
+    de::{MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::{passport, records, Passport};
+
+const KNOWN: [&str; 8] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"];
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PassportRecord {
+    byr: Option<String>,
+    iyr: Option<String>,
+    eyr: Option<String>,
+    hgt: Option<String>,
+    hcl: Option<String>,
+    ecl: Option<String>,
+    pid: Option<String>,
+    cid: Option<String>,
+    // A JSON object, kept in the order the record gave them.
+    #[serde(default, with = "extras_map")]
+    extras: Vec<(String, String)>,
+    // The record as it was written, if it came from the puzzle's format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    original: Option<String>,
+}
+
+// serde_json hands object entries over in the order they're written, so
+// reading them into a list rather than a map keeps that order.
+mod extras_map {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        extras: &[(String, String)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(extras.len()))?;
+        for (key, value) in extras.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+
+    struct ExtrasVisitor;
+
+    impl<'de> Visitor<'de> for ExtrasVisitor {
+        type Value = Vec<(String, String)>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "an object of extra fields")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut extras = vec![];
+            while let Some(entry) = map.next_entry()? {
+                extras.push(entry);
+            }
+            Ok(extras)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(String, String)>, D::Error> {
+        deserializer.deserialize_map(ExtrasVisitor)
+    }
+}
+
+impl From<&Passport> for PassportRecord {
+    fn from(passport: &Passport) -> Self {
+        PassportRecord {
+            byr: passport.byr.clone(),
+            iyr: passport.iyr.clone(),
+            eyr: passport.eyr.clone(),
+            hgt: passport.hgt.clone(),
+            hcl: passport.hcl.clone(),
+            ecl: passport.ecl.clone(),
+            pid: passport.pid.clone(),
+            cid: passport.cid.clone(),
+            extras: passport.extras.clone(),
+            original: Some(passport.original.clone()).filter(|original| !original.is_empty()),
+        }
+    }
+}
+
+// Values run up to the next whitespace in the puzzle's format, and keys are
+// letters and digits, so anything else couldn't be written back out.
+fn check_value(key: &str, value: &str) -> Result<(), String> {
+    if value.is_empty() {
+        return Err(format!("{} has an empty value", key));
+    }
+    if value.contains(char::is_whitespace) {
+        return Err(format!(
+            "{} can't have spaces in its value {:?}",
+            key, value
+        ));
+    }
+    Ok(())
+}
+
+impl PassportRecord {
+    fn into_passport(self) -> Result<Passport, String> {
+        let mut passport = Passport::empty(self.original.as_deref().unwrap_or(""));
+        passport.byr = self.byr;
+        passport.iyr = self.iyr;
+        passport.eyr = self.eyr;
+        passport.hgt = self.hgt;
+        passport.hcl = self.hcl;
+        passport.ecl = self.ecl;
+        passport.pid = self.pid;
+        passport.cid = self.cid;
+        for key in KNOWN.iter() {
+            if let Some(value) = passport.get(key) {
+                check_value(key, value)?;
+            }
+        }
+        for (idx, (key, value)) in self.extras.iter().enumerate() {
+            if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(format!(
+                    "extra field names are letters and digits, not {:?}",
+                    key
+                ));
+            }
+            if KNOWN.contains(&key.as_str()) || self.extras[..idx].iter().any(|(k, _)| k == key) {
+                return Err(format!("{} is given more than once", key));
+            }
+            check_value(key, value)?;
+        }
+        passport.extras = self.extras;
+        Ok(passport)
+    }
+}
+
+pub fn from_native(input: &str) -> Result<Vec<Passport>, String> {
+    records(input)
+        .into_iter()
+        .map(|record| {
+            passport(record.text)
+                .map_err(|e| format!("Record {} (line {}): {}", record.index, record.line, e))
+        })
+        .collect()
+}
+
+// The original text is only written back out if it still says the same thing
+// as the fields, so edits made in JSON or CSV aren't thrown away.
+pub fn to_native(passports: &[Passport]) -> String {
+    passports
+        .iter()
+        .map(|passport| {
+            let keeps_original = !passport.original.is_empty()
+                && super::passport(&passport.original)
+                    .map(|original| {
+                        PassportRecord::from(&original) == PassportRecord::from(passport)
+                    })
+                    .unwrap_or(false);
+            if keeps_original {
+                return passport.original.clone();
+            }
+            KNOWN
+                .iter()
+                .filter_map(|key| passport.get(key).map(|value| format!("{}:{}", key, value)))
+                .chain(
+                    passport
+                        .extras
+                        .iter()
+                        .map(|(key, value)| format!("{}:{}", key, value)),
+                )
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+pub fn to_json(passports: &[Passport]) -> Result<String, String> {
+    let records = passports
+        .iter()
+        .map(PassportRecord::from)
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&records).map_err(|e| e.to_string())
+}
+
+pub fn from_json(input: &str) -> Result<Vec<Passport>, String> {
+    let records: Vec<PassportRecord> =
+        serde_json::from_str(input).map_err(|e| format!("bad passport JSON: {}", e))?;
+    records
+        .into_iter()
+        .enumerate()
+        .map(|(idx, record)| {
+            record
+                .into_passport()
+                .map_err(|e| format!("Record {}: {}", idx + 1, e))
+        })
+        .collect()
+}
+
+// One column per known field, then the extras written as `key:value` pairs
+// separated by spaces, then the original text. Empty cells are missing fields.
+pub fn to_csv(passports: &[Passport]) -> Result<String, String> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    let header = KNOWN.iter().chain(["extras", "original"].iter());
+    writer.write_record(header).map_err(|e| e.to_string())?;
+    for passport in passports.iter() {
+        let record = PassportRecord::from(passport);
+        let extras = record
+            .extras
+            .iter()
+            .map(|(key, value)| format!("{}:{}", key, value))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let row = KNOWN
+            .iter()
+            .map(|key| String::from(passport.get(key).unwrap_or("")))
+            .chain(vec![extras, record.original.unwrap_or_default()]);
+        writer.write_record(row).map_err(|e| e.to_string())?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+pub fn from_csv(input: &str) -> Result<Vec<Passport>, String> {
+    let mut reader = csv::Reader::from_reader(input.as_bytes());
+    let header = reader.headers().map_err(|e| e.to_string())?.clone();
+    let mut passports = vec![];
+    for (idx, row) in reader.records().enumerate() {
+        let row = row.map_err(|e| format!("bad passport CSV: {}", e))?;
+        let mut record = PassportRecord::default();
+        for (column, cell) in header.iter().zip(row.iter()) {
+            let cell = Some(String::from(cell)).filter(|cell| !cell.is_empty());
+            match column {
+                "byr" => record.byr = cell,
+                "iyr" => record.iyr = cell,
+                "eyr" => record.eyr = cell,
+                "hgt" => record.hgt = cell,
+                "hcl" => record.hcl = cell,
+                "ecl" => record.ecl = cell,
+                "pid" => record.pid = cell,
+                "cid" => record.cid = cell,
+                "original" => record.original = cell,
+                "extras" => {
+                    for pair in cell.iter().flat_map(|cell| cell.split_whitespace()) {
+                        let (key, value) = pair.split_once(':').ok_or_else(|| {
+                            format!("Row {}: extras look like key:value, not {}", idx + 1, pair)
+                        })?;
+                        record.extras.push((String::from(key), String::from(value)));
+                    }
+                }
+                _ => return Err(format!("Unknown passport CSV column: {}", column)),
+            }
+        }
+        passports.push(
+            record
+                .into_passport()
+                .map_err(|e| format!("Row {}: {}", idx + 1, e))?,
+        );
+    }
+    Ok(passports)
+}
+
+#[cfg(test)]
+const TEST_INPUT: &str = "\
+ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929 zzz:1";
+
+#[test]
+fn round_trips() -> Result<(), String> {
+    let passports = from_native(TEST_INPUT)?;
+    assert_eq!(to_native(&from_json(&to_json(&passports)?)?), TEST_INPUT);
+    assert_eq!(to_native(&from_csv(&to_csv(&passports)?)?), TEST_INPUT);
+
+    let csv = to_csv(&passports)?;
+    assert_eq!(
+        csv.lines().next(),
+        Some("byr,iyr,eyr,hgt,hcl,ecl,pid,cid,extras,original")
+    );
+    assert!(csv.contains(",zzz:1,"));
+    Ok(())
+}
+
+#[test]
+fn imports_without_original() -> Result<(), String> {
+    let passports = from_json(r#"[{"pid": "012345678", "extras": {"zzz": "1"}}]"#)?;
+    assert_eq!(to_native(&passports), "pid:012345678 zzz:1");
+
+    // Extras keep their order when there's no original text to fall back on.
+    let mut passports = from_native("zzz:1 pid:012345678 aaa:2")?;
+    passports[0].original.clear();
+    assert_eq!(
+        to_native(&from_json(&to_json(&passports)?)?),
+        "pid:012345678 zzz:1 aaa:2"
+    );
+    assert_eq!(
+        to_native(&from_csv(&to_csv(&passports)?)?),
+        "pid:012345678 zzz:1 aaa:2"
+    );
+
+    // An edited field wins over the original text.
+    let passports = from_csv("pid,hgt,original\n012345678,,pid:999999999 hgt:60in\n")?;
+    assert_eq!(to_native(&passports), "pid:012345678");
+
+    assert!(from_csv("pid,passport\n1,2\n").is_err());
+
+    // Nothing the puzzle's format couldn't hold comes in.
+    for json in [
+        r#"[{"pid": "1 2"}]"#,
+        r#"[{"hgt": ""}]"#,
+        r#"[{"extras": {"a b": "x"}}]"#,
+        r#"[{"extras": {"pid": "1"}}]"#,
+        r#"[{"extras": {"zzz": "1", "zzz": "2"}}]"#,
+        r#"[{"extras": {"zzz": ""}}]"#,
+    ]
+    .iter()
+    {
+        assert!(from_json(json).is_err(), "{}", json);
+    }
+    assert_eq!(
+        from_json(r#"[{"pid": "1"}, {"pid": "1 2"}]"#).unwrap_err(),
+        "Record 2: pid can't have spaces in its value \"1 2\""
+    );
+    assert_eq!(
+        from_csv("pid,extras\n1,pid:2\n").unwrap_err(),
+        "Row 1: pid is given more than once"
+    );
+    assert!(from_csv("pid,extras\n1,:2\n").is_err());
+    assert!(from_native("pid:1\n\nhgt 5'10\"").is_err());
+    Ok(())
+}
@@ -0,0 +1,271 @@
+// Made up passport batches for stress testing, along with how many of them
+// part 1 and part 2 should count under the puzzle's rules. The values are made
+// to pass or fail those rules, so the counts don't hold for other profiles.
+use std::fmt;
+
+// Small and predictable, so a seed always gives the same batch.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // One round of splitmix64, so small seeds (even 0) still start from a
+        // state with plenty of bits set.
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Rng((z ^ (z >> 31)) | 1)
+    }
+
+    // xorshift64*
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn between(&mut self, low: usize, high: usize) -> usize {
+        low + self.below(high - low + 1)
+    }
+
+    fn pick<'a>(&mut self, choices: &[&'a str]) -> &'a str {
+        choices[self.below(choices.len())]
+    }
+
+    fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.below(idx + 1));
+        }
+    }
+}
+
+// How often each kind of passport comes up, relative to the others.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mix {
+    pub valid: usize,
+    // Missing at least one required field.
+    pub missing: usize,
+    // Every field is there but at least one breaks a part 2 rule.
+    pub out_of_range: usize,
+}
+
+impl Default for Mix {
+    fn default() -> Self {
+        Mix {
+            valid: 50,
+            missing: 25,
+            out_of_range: 25,
+        }
+    }
+}
+
+pub struct Batch {
+    // In the puzzle's format.
+    pub text: String,
+    // What part 1 and part 2 count under the puzzle's rules.
+    pub lax: usize,
+    pub strict: usize,
+}
+
+impl fmt::Display for Batch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+const REQUIRED: [&str; 7] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
+const EYE_COLORS: [&str; 7] = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
+fn good_value(rng: &mut Rng, key: &str) -> String {
+    let hex = |rng: &mut Rng| format!("#{:06x}", rng.below(0x100_0000));
+    match key {
+        "byr" => rng.between(1920, 2002).to_string(),
+        "iyr" => rng.between(2010, 2020).to_string(),
+        "eyr" => rng.between(2020, 2030).to_string(),
+        "hgt" if rng.chance(50) => format!("{}cm", rng.between(150, 193)),
+        "hgt" => format!("{}in", rng.between(59, 76)),
+        "hcl" => hex(rng),
+        "ecl" => String::from(rng.pick(&EYE_COLORS)),
+        "pid" => format!("{:09}", rng.below(1_000_000_000)),
+        _ => rng.between(100, 350).to_string(),
+    }
+}
+
+// Always breaks the puzzle's rules for the field.
+fn bad_value(rng: &mut Rng, key: &str) -> String {
+    let year = |rng: &mut Rng, low: usize, high: usize| {
+        if rng.chance(50) {
+            rng.between(low - 20, low - 1).to_string()
+        } else {
+            rng.between(high + 1, high + 20).to_string()
+        }
+    };
+    match key {
+        "byr" => year(rng, 1920, 2002),
+        "iyr" => year(rng, 2010, 2020),
+        "eyr" => year(rng, 2020, 2030),
+        "hgt" => match rng.below(3) {
+            0 => format!("{}cm", rng.between(194, 250)),
+            1 => format!("{}in", rng.between(30, 58)),
+            _ => rng.between(59, 193).to_string(),
+        },
+        "hcl" => match rng.below(2) {
+            0 => format!("{:06x}", rng.below(0x100_0000)),
+            _ => format!("#{:06X}", rng.between(0xa0_0000, 0xff_ffff)),
+        },
+        "ecl" => String::from(rng.pick(&["blue", "zzz", "gray", "hazel"])),
+        "pid" if rng.chance(50) => format!("{:08}", rng.below(100_000_000)),
+        "pid" => format!("{:010}", rng.below(10_000_000_000)),
+        _ => good_value(rng, key),
+    }
+}
+
+enum Case {
+    Valid,
+    Missing,
+    OutOfRange,
+}
+
+fn record(rng: &mut Rng, case: &Case) -> String {
+    let mut keys = REQUIRED.to_vec();
+    if rng.chance(50) {
+        keys.push("cid");
+    }
+    rng.shuffle(&mut keys);
+    let mut broken = vec![];
+    match case {
+        Case::Valid => {}
+        Case::Missing => {
+            for _ in 0..rng.between(1, 3) {
+                let idx = keys.iter().position(|key| *key != "cid").unwrap();
+                keys.remove(idx);
+            }
+        }
+        Case::OutOfRange => {
+            let mut required = REQUIRED.to_vec();
+            rng.shuffle(&mut required);
+            broken.extend_from_slice(&required[..rng.between(1, 2)]);
+        }
+    }
+    let mut record = String::new();
+    for (idx, key) in keys.iter().enumerate() {
+        let value = if broken.contains(key) {
+            bad_value(rng, key)
+        } else {
+            good_value(rng, key)
+        };
+        if idx > 0 {
+            record.push(if rng.chance(25) { '\n' } else { ' ' });
+        }
+        record.push_str(&format!("{}:{}", key, value));
+    }
+    record
+}
+
+pub fn generate(count: usize, mix: Mix, seed: u64) -> Result<Batch, String> {
+    let total = mix
+        .valid
+        .checked_add(mix.missing)
+        .and_then(|total| total.checked_add(mix.out_of_range))
+        .ok_or_else(|| String::from("The mix weights are too big to add up, buddy."))?;
+    if total == 0 {
+        return Err(String::from(
+            "The mix needs at least one kind of passport, buddy.",
+        ));
+    }
+    let mut rng = Rng::new(seed);
+    let mut records = vec![];
+    let (mut lax, mut strict) = (0, 0);
+    for _ in 0..count {
+        let roll = rng.below(total);
+        let case = if roll < mix.valid {
+            lax += 1;
+            Case::Valid
+        } else if roll < mix.valid + mix.missing {
+            Case::Missing
+        } else {
+            lax += 1;
+            Case::OutOfRange
+        };
+        let record = record(&mut rng, &case);
+        // The typed fields are the puzzle's rules, so they have the last word
+        // on what part 2 should count.
+        if super::passport(&record)?.is_valid_2() {
+            strict += 1;
+        }
+        records.push(record);
+    }
+    Ok(Batch {
+        text: records.join("\n\n"),
+        lax,
+        strict,
+    })
+}
+
+#[test]
+fn generated_counts_match_the_solvers() -> Result<(), String> {
+    use super::{part1_solution, part2_solution, rules::Rules};
+
+    let rules = Rules::default_profile()?;
+    for seed in 0..20 {
+        let batch = generate(200, Mix::default(), seed)?;
+        assert_eq!(
+            part1_solution(&batch.text, &rules)?,
+            batch.lax,
+            "seed {}",
+            seed
+        );
+        assert_eq!(
+            part2_solution(&batch.text, &rules)?,
+            batch.strict,
+            "seed {}",
+            seed
+        );
+    }
+
+    let again = generate(50, Mix::default(), 7)?;
+    assert_eq!(generate(50, Mix::default(), 7)?.text, again.text);
+    assert_ne!(generate(50, Mix::default(), 8)?.text, again.text);
+
+    let all_missing = Mix {
+        valid: 0,
+        missing: 1,
+        out_of_range: 0,
+    };
+    let batch = generate(30, all_missing, 1)?;
+    assert_eq!((batch.lax, batch.strict), (0, 0));
+    let all_valid = Mix {
+        valid: 1,
+        missing: 0,
+        out_of_range: 0,
+    };
+    let batch = generate(30, all_valid, 1)?;
+    assert_eq!((batch.lax, batch.strict), (30, 30));
+    assert!(generate(
+        1,
+        Mix {
+            missing: 0,
+            ..all_missing
+        },
+        1
+    )
+    .is_err());
+    assert!(generate(
+        1,
+        Mix {
+            valid: usize::MAX,
+            ..all_missing
+        },
+        1
+    )
+    .is_err());
+    Ok(())
+}
@@ -0,0 +1,254 @@
+// Heights are kept as a whole number of micrometers, which every unit we read
+// converts to exactly, along with the unit they were written in.
+use std::{convert::TryFrom, fmt};
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, digit1},
+    combinator::{all_consuming, map, map_res, opt, recognize},
+    sequence::{pair, tuple},
+    IResult,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Unit {
+    Millimeter,
+    Centimeter,
+    Meter,
+    Inch,
+    // Written like 5'10".
+    FeetAndInches,
+}
+
+impl Unit {
+    fn from(s: &str) -> Result<Unit, String> {
+        Ok(match s {
+            "mm" => Unit::Millimeter,
+            "cm" => Unit::Centimeter,
+            "m" => Unit::Meter,
+            "in" => Unit::Inch,
+            _ => return Err(String::from("Not a valid unit")),
+        })
+    }
+
+    // Feet and inches count in inches.
+    fn micrometers(self) -> u64 {
+        match self {
+            Unit::Millimeter => 1_000,
+            Unit::Centimeter => 10_000,
+            Unit::Meter => 1_000_000,
+            Unit::Inch | Unit::FeetAndInches => 25_400,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Height {
+    micrometers: u64,
+    unit: Unit,
+}
+
+// A number like 183 or 1.83 and its unit, or feet and inches.
+enum Written<'a> {
+    Decimal(&'a str, Unit),
+    FeetAndInches(&'a str, &'a str),
+}
+
+fn decimal(input: &str) -> IResult<&str, &str> {
+    recognize(pair(digit1, opt(pair(char('.'), digit1))))(input)
+}
+
+fn written(input: &str) -> IResult<&str, Written<'_>> {
+    alt((
+        map(
+            tuple((digit1, char('\''), digit1, char('"'))),
+            |(feet, _, inches, _)| Written::FeetAndInches(feet, inches),
+        ),
+        map(
+            pair(
+                decimal,
+                map_res(alt((tag("mm"), tag("cm"), tag("m"), tag("in"))), Unit::from),
+            ),
+            |(number, unit)| Written::Decimal(number, unit),
+        ),
+    ))(input)
+}
+
+// Scales a decimal like 1.83 up to micrometers without going through floats.
+fn to_micrometers(number: &str, unit: Unit) -> Result<u64, String> {
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let too_tall = || format!("{} is too tall, buddy", number);
+    let mantissa = format!("{}{}", whole, fraction)
+        .parse::<u64>()
+        .map_err(|_| too_tall())?;
+    let scale = 10u64
+        .checked_pow(fraction.len() as u32)
+        .ok_or_else(too_tall)?;
+    let scaled = mantissa
+        .checked_mul(unit.micrometers())
+        .ok_or_else(too_tall)?;
+    if scaled % scale != 0 {
+        return Err(format!("{} is more precise than a micrometer", number));
+    }
+    Ok(scaled / scale)
+}
+
+impl Height {
+    // Any height at all, see TryFrom for one the puzzle would allow.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (_, written) = all_consuming(written)(s).map_err(|_| {
+            format!(
+                "{:?} isn't a height like 183cm, 1830mm, 1.83m, 72in or 6'0\"",
+                s
+            )
+        })?;
+        match written {
+            Written::Decimal(number, unit) => Ok(Height {
+                micrometers: to_micrometers(number, unit)?,
+                unit,
+            }),
+            Written::FeetAndInches(feet, inches) => {
+                let inches = inches
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|inches| *inches < 12)
+                    .ok_or_else(|| format!("{:?} has more than 11 inches", s))?;
+                let feet = to_micrometers(feet, Unit::Inch)?;
+                Ok(Height {
+                    micrometers: feet
+                        .checked_mul(12)
+                        .and_then(|feet| feet.checked_add(inches * Unit::Inch.micrometers()))
+                        .ok_or_else(|| format!("{} is too tall, buddy", s))?,
+                    unit: Unit::FeetAndInches,
+                })
+            }
+        }
+    }
+
+    pub fn micrometers(&self) -> u64 {
+        self.micrometers
+    }
+
+    // The same height, written in another unit.
+    pub fn to(self, unit: Unit) -> Height {
+        Height { unit, ..self }
+    }
+
+    pub fn between(&self, shortest: &Height, tallest: &Height) -> Result<(), String> {
+        if self.micrometers < shortest.micrometers || self.micrometers > tallest.micrometers {
+            return Err(format!(
+                "{} isn't between {} and {}",
+                self,
+                shortest.to(self.unit),
+                tallest.to(self.unit)
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Only what the puzzle allows: a whole number of centimeters from 150 to 193,
+// or of inches from 59 to 76.
+impl TryFrom<&str> for Height {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let height = Height::parse(s)?;
+        let (shortest, tallest) = match height.unit {
+            Unit::Centimeter => (150, 193),
+            Unit::Inch => (59, 76),
+            _ => return Err(format!("{:?} isn't in cm or in", s)),
+        };
+        if !s[..s.len() - 2].chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!(
+                "{:?} isn't a whole number of {}",
+                s,
+                &s[s.len() - 2..]
+            ));
+        }
+        let bound = |n: u64| Height {
+            micrometers: n * height.unit.micrometers(),
+            unit: height.unit,
+        };
+        height.between(&bound(shortest), &bound(tallest))?;
+        Ok(height)
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let per_unit = self.unit.micrometers();
+        let value = self.micrometers as f64 / per_unit as f64;
+        match self.unit {
+            Unit::FeetAndInches if self.micrometers.is_multiple_of(per_unit) => {
+                let inches = self.micrometers / per_unit;
+                write!(f, "{}'{}\"", inches / 12, inches % 12)
+            }
+            Unit::Millimeter => write!(f, "{}mm", value),
+            Unit::Centimeter => write!(f, "{}cm", value),
+            Unit::Meter => write!(f, "{}m", value),
+            Unit::Inch | Unit::FeetAndInches => write!(f, "{}in", value),
+        }
+    }
+}
+
+#[test]
+fn heights() -> Result<(), String> {
+    let parsed = [
+        "183cm", "1830mm", "1.83m", "72in", "6'0\"", "5'10\"", "175.5cm",
+    ]
+    .iter()
+    .map(|s| Height::parse(s).map(|height| height.micrometers()))
+    .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        parsed,
+        vec![1_830_000, 1_830_000, 1_830_000, 1_828_800, 1_828_800, 1_778_000, 1_755_000]
+    );
+    assert_eq!(
+        Height::parse("1.83m")?.to(Unit::Centimeter).to_string(),
+        "183cm"
+    );
+    assert_eq!(
+        Height::parse("70in")?.to(Unit::FeetAndInches).to_string(),
+        "5'10\""
+    );
+    assert_eq!(
+        Height::parse("1m")?.to(Unit::Inch).to_string(),
+        "39.37007874015748in"
+    );
+
+    // The puzzle's limits, in both of its units.
+    for (height, allowed) in [
+        ("149cm", false),
+        ("150cm", true),
+        ("193cm", true),
+        ("194cm", false),
+        ("58in", false),
+        ("59in", true),
+        ("76in", true),
+        ("77in", false),
+        ("149.9cm", false),
+        ("193.04cm", false),
+        ("1.6m", false),
+        ("5'0\"", false),
+    ]
+    .iter()
+    {
+        assert_eq!(Height::try_from(*height).is_ok(), *allowed, "{}", height);
+    }
+
+    assert!(Height::parse("190").is_err());
+    assert!(Height::parse("5'12\"").is_err());
+    assert!(Height::parse("1.0000001m").is_err());
+    assert!(Height::parse("99999999999999999999cm").is_err());
+    assert_eq!(
+        Height::try_from("194cm").unwrap_err(),
+        "194cm isn't between 150cm and 193cm"
+    );
+    assert_eq!(
+        Height::try_from("2m").unwrap_err(),
+        "\"2m\" isn't in cm or in"
+    );
+    Ok(())
+}
@@ -0,0 +1,175 @@
+// Paste in a passport, finish it with a blank line, and see what's wrong with
+// each field.
+use std::io::{BufRead, Write};
+
+use super::{
+    fields,
+    report::{Problem, Report},
+    rules::Rules,
+    suggest::Suggestion,
+};
+
+const GREEN: u8 = 32;
+const RED: u8 = 31;
+const YELLOW: u8 = 33;
+
+fn paint(text: &str, code: u8, color: bool) -> String {
+    if color {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        String::from(text)
+    }
+}
+
+fn describe(problem: &Problem) -> String {
+    match &problem.suggestion {
+        Some(Suggestion::Replace(value)) => format!("{} (try {})", problem.reason, value),
+        Some(Suggestion::Hint(hint)) => format!("{} ({})", problem.reason, hint),
+        None => problem.reason.clone(),
+    }
+}
+
+// Every field in the order it was written, then the required ones that
+// weren't. Only the last of a repeated key is judged, the ones before it were
+// overridden.
+pub fn breakdown(record: &str, rules: &Rules, color: bool) -> String {
+    let report = Report::new(record, rules);
+    let entry = match report.entries.first() {
+        Some(entry) => entry,
+        None => return String::new(),
+    };
+    let mut lines = vec![format!(
+        "{} (lax {}, strict {})",
+        entry.kind, entry.lax, entry.strict
+    )];
+    if let Some(e) = &entry.error {
+        lines.push(format!("  {}", paint(e, RED, color)));
+        return lines.join("\n");
+    }
+    let fields = fields(record).unwrap_or_default();
+    for (idx, field) in fields.iter().enumerate() {
+        let overridden = fields[idx + 1..].iter().any(|later| later.key == field.key);
+        let problem = entry.problems.iter().find(|p| p.field == field.key);
+        lines.push(match (overridden, problem) {
+            (true, _) => format!(
+                "  {} {}:{} {}",
+                paint("!", YELLOW, color),
+                field.key,
+                field.value,
+                paint(
+                    &format!("overridden by a later {}", field.key),
+                    YELLOW,
+                    color
+                )
+            ),
+            (false, Some(problem)) => format!(
+                "  {} {}:{} {}",
+                paint("✗", RED, color),
+                field.key,
+                field.value,
+                paint(&describe(problem), RED, color)
+            ),
+            (false, None) => format!(
+                "  {} {}:{}",
+                paint("✓", GREEN, color),
+                field.key,
+                field.value
+            ),
+        });
+    }
+    let written = |problem: &&Problem| fields.iter().any(|field| field.key == problem.field);
+    for problem in entry.problems.iter().filter(|p| !written(p)) {
+        lines.push(format!(
+            "  {} {} {}",
+            paint("✗", RED, color),
+            problem.field,
+            paint(&describe(problem), RED, color)
+        ));
+    }
+    for duplicate in entry.duplicates.iter() {
+        lines.push(format!(
+            "  {} {} {}",
+            paint("!", YELLOW, color),
+            duplicate.field,
+            paint(&duplicate.reason, YELLOW, color)
+        ));
+    }
+    lines.join("\n")
+}
+
+// Reads records until the input runs out, printing a breakdown after each one.
+pub fn run(
+    input: impl BufRead,
+    output: &mut impl Write,
+    rules: &Rules,
+    color: bool,
+) -> Result<(), String> {
+    let io = |e: std::io::Error| e.to_string();
+    let prompt = |output: &mut dyn Write, record: &str| -> Result<(), String> {
+        let prompt = if record.is_empty() {
+            "passport> "
+        } else {
+            "........> "
+        };
+        write!(output, "{}", prompt).map_err(io)?;
+        output.flush().map_err(io)
+    };
+    let mut record = String::new();
+    prompt(output, &record)?;
+    for line in input.lines() {
+        let line = line.map_err(io)?;
+        if line.trim().is_empty() {
+            if !record.is_empty() {
+                writeln!(output, "{}", breakdown(&record, rules, color)).map_err(io)?;
+                record.clear();
+            }
+        } else {
+            record.push_str(&line);
+            record.push('\n');
+        }
+        prompt(output, &record)?;
+    }
+    if !record.is_empty() {
+        writeln!(output, "\n{}", breakdown(&record, rules, color)).map_err(io)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn breakdown_per_field() -> Result<(), String> {
+    let rules = Rules::default_profile()?;
+    let input = "\
+ecl:gry pid:86003332 eyr:2020 hcl:fffffd
+iyr:2017 hgt:183cm ecl:blu
+
+hgt 183cm
+";
+    let mut output = vec![];
+    run(input.as_bytes(), &mut output, &rules, false)?;
+    let output = String::from_utf8(output).map_err(|e| e.to_string())?;
+    assert_eq!(
+        output,
+        "\
+passport> ........> ........> incomplete (lax invalid, strict invalid)
+  ! ecl:gry overridden by a later ecl
+  ✗ pid:86003332 \"86003332\" doesn't match ^[0-9]{9}$ (1 digit short)
+  ✓ eyr:2020
+  ✗ hcl:fffffd \"fffffd\" doesn't match ^#[0-9a-f]{6}$ (try #fffffd)
+  ✓ iyr:2017
+  ✓ hgt:183cm
+  ✓ ecl:blu
+  ✗ byr missing
+  ! ecl given more than once, the last one was used
+passport> ........> 
+invalid (lax invalid, strict invalid)
+  couldn't parse \"hgt\" at offset 0
+"
+    );
+    assert!(breakdown("byr:1900", &rules, true).contains("\x1b[31m✗\x1b[0m byr:1900"));
+
+    // Only the value that counts is blamed.
+    let repeated = breakdown("ecl:gry ecl:zzz", &rules, false);
+    assert!(repeated.contains("  ! ecl:gry overridden by a later ecl\n"));
+    assert!(repeated.contains("  ✗ ecl:zzz \"zzz\" isn't one of"));
+    Ok(())
+}
@@ -0,0 +1,418 @@
+// What's wrong with every passport in a batch, so the records can be fixed at
+// the source.
+use std::{collections::BTreeMap, convert::TryFrom, fmt};
+
+use serde::Serialize;
+
+use super::{
+    passport, records,
+    rules::{Policy, Rules},
+    suggest::{suggest, Suggestion},
+    validated::ValidPassport,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Valid,
+    Invalid,
+}
+
+impl Verdict {
+    fn from(valid: bool) -> Self {
+        if valid {
+            Verdict::Valid
+        } else {
+            Verdict::Invalid
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Valid => write!(f, "valid"),
+            Verdict::Invalid => write!(f, "invalid"),
+        }
+    }
+}
+
+// What kind of document a record turned out to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    // Passes the rules and has a cid.
+    Passport,
+    // Passes the rules but has no cid, which only the North Pole hands out.
+    NorthPoleCredential,
+    // Missing a required field.
+    Incomplete,
+    // Has everything, but something fails the rules, or it didn't parse.
+    Invalid,
+}
+
+impl Kind {
+    const ALL: [Kind; 4] = [
+        Kind::Passport,
+        Kind::NorthPoleCredential,
+        Kind::Incomplete,
+        Kind::Invalid,
+    ];
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::Passport => write!(f, "passport"),
+            Kind::NorthPoleCredential => write!(f, "North Pole credential"),
+            Kind::Incomplete => write!(f, "incomplete"),
+            Kind::Invalid => write!(f, "invalid"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Problem {
+    pub field: String,
+    pub reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<Suggestion>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Entry {
+    // Counting from 1, like the line it starts on.
+    pub record: usize,
+    pub line: usize,
+    // Lax only wants the required fields to be there, strict wants every rule
+    // to pass.
+    pub lax: Verdict,
+    pub strict: Verdict,
+    pub kind: Kind,
+    // Set when the record couldn't be parsed at all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub problems: Vec<Problem>,
+    // Fields given twice in the record, and a pid that other records share.
+    // Whether these count against the verdicts is up to the rules.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub duplicates: Vec<Problem>,
+    // The passport's typed fields, when it passes the rules. Profiles looser
+    // than the puzzle's can pass passports that don't convert, which get none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typed: Option<ValidPassport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub entries: Vec<Entry>,
+    // How many records there are of each kind.
+    pub counts: BTreeMap<Kind, usize>,
+}
+
+impl Report {
+    pub fn new(input: &str, rules: &Rules) -> Self {
+        let parsed = records(input)
+            .into_iter()
+            .map(|record| (record.index, record.line, passport(record.text)))
+            .collect::<Vec<_>>();
+        let mut pids = BTreeMap::new();
+        for (index, _, passport) in parsed.iter() {
+            if let Some(pid) = passport.as_ref().ok().and_then(|p| p.pid.as_deref()) {
+                pids.entry(pid).or_insert_with(Vec::new).push(*index);
+            }
+        }
+        let entries = parsed
+            .iter()
+            .map(|(index, line, passport)| {
+                let mut entry = Entry {
+                    record: *index,
+                    line: *line,
+                    lax: Verdict::Invalid,
+                    strict: Verdict::Invalid,
+                    kind: Kind::Invalid,
+                    error: None,
+                    problems: vec![],
+                    duplicates: vec![],
+                    typed: None,
+                };
+                let passport = match passport {
+                    Ok(passport) => passport,
+                    Err(e) => {
+                        entry.error = Some(e.clone());
+                        return entry;
+                    }
+                };
+                let problems = rules.check(passport).err().unwrap_or_default();
+                entry.problems = problems
+                    .into_iter()
+                    .map(|(field, reason)| {
+                        let suggestion = passport
+                            .get(&field)
+                            .and_then(|value| suggest(rules, &field, value));
+                        Problem {
+                            field,
+                            reason,
+                            suggestion,
+                        }
+                    })
+                    .collect();
+                for key in passport.duplicates.iter() {
+                    entry.duplicates.push(Problem {
+                        field: key.clone(),
+                        reason: String::from("given more than once, the last one was used"),
+                        suggestion: None,
+                    });
+                }
+                if let Some(pid) = passport.pid.as_deref() {
+                    let others = pids[pid]
+                        .iter()
+                        .filter(|other| *other != index)
+                        .map(|other| other.to_string())
+                        .collect::<Vec<_>>();
+                    if !others.is_empty() {
+                        entry.duplicates.push(Problem {
+                            field: String::from("pid"),
+                            reason: format!("{} is also used by record {}", pid, others.join(", ")),
+                            suggestion: None,
+                        });
+                    }
+                }
+                let rejected = rules.duplicates == Policy::Reject && !entry.duplicates.is_empty();
+                let complete = rules.missing(passport).is_empty();
+                entry.lax = Verdict::from(complete && !rejected);
+                entry.strict = Verdict::from(entry.problems.is_empty() && !rejected);
+                // A rejected duplicate has everything it needs, so it's invalid
+                // rather than incomplete.
+                entry.kind = match (complete, entry.strict) {
+                    (false, _) => Kind::Incomplete,
+                    (_, Verdict::Invalid) => Kind::Invalid,
+                    _ if passport.cid.is_some() => Kind::Passport,
+                    _ => Kind::NorthPoleCredential,
+                };
+                if entry.strict == Verdict::Valid {
+                    entry.typed = ValidPassport::try_from(passport).ok();
+                }
+                entry
+            })
+            .collect::<Vec<_>>();
+        let counts = Kind::ALL
+            .iter()
+            .map(|kind| {
+                let count = entries.iter().filter(|entry| entry.kind == *kind).count();
+                (*kind, count)
+            })
+            .collect();
+        Report { entries, counts }
+    }
+
+    pub fn count(&self, verdict: impl Fn(&Entry) -> Verdict) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| verdict(entry) == Verdict::Valid)
+            .count()
+    }
+
+    // Something like "passport 1, North Pole credential 2, incomplete 0,
+    // invalid 3".
+    pub fn describe_counts(&self) -> String {
+        self.counts
+            .iter()
+            .map(|(kind, count)| format!("{} {}", kind, count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for entry in self.entries.iter() {
+            writeln!(
+                f,
+                "Record {} (line {}): {}, lax {}, strict {}",
+                entry.record, entry.line, entry.kind, entry.lax, entry.strict
+            )?;
+            if let Some(e) = &entry.error {
+                writeln!(f, "  {}", e)?;
+            }
+            for problem in entry.problems.iter() {
+                write!(f, "  {}: {}", problem.field, problem.reason)?;
+                match &problem.suggestion {
+                    Some(Suggestion::Replace(value)) => writeln!(f, " (try {})", value)?,
+                    Some(Suggestion::Hint(hint)) => writeln!(f, " ({})", hint)?,
+                    None => writeln!(f)?,
+                }
+            }
+            for duplicate in entry.duplicates.iter() {
+                writeln!(f, "  duplicate {}: {}", duplicate.field, duplicate.reason)?;
+            }
+        }
+        writeln!(
+            f,
+            "{} records: {} valid under lax rules, {} under strict rules",
+            self.entries.len(),
+            self.count(|entry| entry.lax),
+            self.count(|entry| entry.strict)
+        )?;
+        write!(f, "{}", self.describe_counts())
+    }
+}
+
+#[test]
+fn duplicates() -> Result<(), String> {
+    let input = "\
+ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+ecl:gry pid:860033327 eyr:2020 hcl:#fffffd ecl:brn
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+ecl:gry pid:760753108 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+pid:860033327";
+    let mut rules = Rules::default_profile()?;
+    let report = Report::new(input, &rules);
+    assert_eq!(report.count(|entry| entry.strict), 3);
+    assert_eq!(
+        report.entries[1].duplicates,
+        vec![
+            Problem {
+                field: String::from("ecl"),
+                reason: String::from("given more than once, the last one was used"),
+                suggestion: None
+            },
+            Problem {
+                field: String::from("pid"),
+                reason: String::from("860033327 is also used by record 1, 4"),
+                suggestion: None
+            }
+        ]
+    );
+    assert!(report.entries[2].duplicates.is_empty());
+
+    rules.duplicates = Policy::Reject;
+    let report = Report::new(input, &rules);
+    assert_eq!(
+        report
+            .entries
+            .iter()
+            .map(|entry| (entry.lax, entry.strict))
+            .collect::<Vec<_>>(),
+        vec![
+            (Verdict::Invalid, Verdict::Invalid),
+            (Verdict::Invalid, Verdict::Invalid),
+            (Verdict::Valid, Verdict::Valid),
+            (Verdict::Invalid, Verdict::Invalid),
+        ]
+    );
+    assert_eq!(
+        report
+            .entries
+            .iter()
+            .map(|entry| entry.kind)
+            .collect::<Vec<_>>(),
+        vec![
+            Kind::Invalid,
+            Kind::Invalid,
+            Kind::Passport,
+            Kind::Incomplete
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn report_test_input() -> Result<(), String> {
+    let input = "\
+ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179in
+
+hgt 5'10\"";
+    let report = Report::new(input, &Rules::default_profile()?);
+    assert_eq!(
+        report
+            .entries
+            .iter()
+            .map(|entry| (entry.record, entry.line, entry.lax, entry.strict))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, 1, Verdict::Valid, Verdict::Valid),
+            (2, 5, Verdict::Invalid, Verdict::Invalid),
+            (3, 8, Verdict::Valid, Verdict::Invalid),
+            (4, 13, Verdict::Invalid, Verdict::Invalid),
+        ]
+    );
+    assert_eq!(
+        report.entries[2].problems,
+        vec![Problem {
+            field: String::from("hgt"),
+            reason: String::from("179in isn't between 59in and 76in"),
+            suggestion: None
+        }]
+    );
+    assert_eq!(
+        report
+            .to_string()
+            .lines()
+            .skip(1)
+            .take(2)
+            .collect::<Vec<_>>(),
+        vec![
+            "Record 2 (line 5): incomplete, lax invalid, strict invalid",
+            "  hgt: missing"
+        ]
+    );
+    assert_eq!(
+        report
+            .entries
+            .iter()
+            .map(|entry| entry.kind)
+            .collect::<Vec<_>>(),
+        vec![
+            Kind::Passport,
+            Kind::Incomplete,
+            Kind::Invalid,
+            Kind::Invalid
+        ]
+    );
+    assert_eq!(
+        report.describe_counts(),
+        "passport 1, North Pole credential 0, incomplete 1, invalid 2"
+    );
+    assert!(report.to_json()?.contains("\"kind\": \"incomplete\""));
+    assert!(report.entries[1..]
+        .iter()
+        .all(|entry| entry.typed.is_none()));
+    let typed = serde_json::to_value(&report.entries[0].typed).map_err(|e| e.to_string())?;
+    assert_eq!(
+        typed,
+        serde_json::json!({
+            "byr": 1937,
+            "iyr": 2017,
+            "eyr": 2020,
+            "hgt": "183cm",
+            "hcl": "#fffffd",
+            "ecl": "gry",
+            "pid": "860033327",
+            "cid": "147"
+        })
+    );
+    assert!(report
+        .to_json()?
+        .contains("\"error\": \"couldn't parse \\\"hgt\\\" at offset 0\""));
+    Ok(())
+}
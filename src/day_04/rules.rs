@@ -0,0 +1,354 @@
+// Passport rules that are read from a TOML or JSON file instead of being baked
+// into the code. See default_rules.toml for what a profile looks like.
+use std::{collections::BTreeMap, fs, ops::RangeInclusive, path::Path};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use super::{height::Height, Passport};
+
+const DEFAULT_PROFILE: &str = include_str!("default_rules.toml");
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+    #[serde(default)]
+    required: Vec<String>,
+    #[serde(default)]
+    fields: BTreeMap<String, FieldProfile>,
+    #[serde(default)]
+    duplicates: Policy,
+}
+
+// What to do with a record that gives a field twice, or a passport ID that
+// another record in the batch also uses.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Policy {
+    // Report it, but leave the verdicts alone.
+    #[default]
+    Warn,
+    // Report it and call the passport invalid.
+    Reject,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FieldProfile {
+    pattern: Option<String>,
+    one_of: Option<Vec<String>>,
+    range: Option<(i64, i64)>,
+    units: Option<BTreeMap<String, (i64, i64)>>,
+    height: Option<(String, String)>,
+}
+
+#[derive(Debug)]
+enum Rule {
+    Pattern(Regex),
+    OneOf(Vec<String>),
+    Range(RangeInclusive<i64>),
+    Units(Vec<(String, RangeInclusive<i64>)>),
+    Height(Height, Height),
+}
+
+fn range(name: &str, (from, to): (i64, i64)) -> Result<RangeInclusive<i64>, String> {
+    if from > to {
+        return Err(format!("{}: the range {}..={} is empty", name, from, to));
+    }
+    Ok(from..=to)
+}
+
+impl Rule {
+    fn compile(name: &str, field: FieldProfile) -> Result<Vec<Rule>, String> {
+        let mut rules = vec![];
+        if let Some(pattern) = field.pattern {
+            let regex = Regex::new(&pattern)
+                .map_err(|e| format!("{}: bad pattern {:?}: {}", name, pattern, e))?;
+            rules.push(Rule::Pattern(regex));
+        }
+        if let Some(choices) = field.one_of {
+            rules.push(Rule::OneOf(choices));
+        }
+        if let Some(bounds) = field.range {
+            rules.push(Rule::Range(range(name, bounds)?));
+        }
+        if let Some(units) = field.units {
+            let units = units
+                .into_iter()
+                .map(|(unit, bounds)| Ok((unit, range(name, bounds)?)))
+                .collect::<Result<_, String>>()?;
+            rules.push(Rule::Units(units));
+        }
+        if let Some((shortest, tallest)) = field.height {
+            let parse =
+                |height: &str| Height::parse(height).map_err(|e| format!("{}: {}", name, e));
+            let (shortest, tallest) = (parse(&shortest)?, parse(&tallest)?);
+            if shortest.micrometers() > tallest.micrometers() {
+                return Err(format!(
+                    "{}: nobody is between {} and {}",
+                    name, shortest, tallest
+                ));
+            }
+            rules.push(Rule::Height(shortest, tallest));
+        }
+        Ok(rules)
+    }
+
+    fn check(&self, value: &str) -> Result<(), String> {
+        let number = |n: &str| {
+            n.parse::<i64>()
+                .map_err(|_| format!("{:?} isn't a number", n))
+        };
+        match self {
+            Rule::Pattern(regex) if !regex.is_match(value) => {
+                Err(format!("{:?} doesn't match {}", value, regex))
+            }
+            Rule::OneOf(choices) if !choices.iter().any(|choice| choice == value) => {
+                Err(format!("{:?} isn't one of {}", value, choices.join(", ")))
+            }
+            Rule::Range(range) => {
+                let n = number(value)?;
+                if !range.contains(&n) {
+                    return Err(format!(
+                        "{} isn't between {} and {}",
+                        n,
+                        range.start(),
+                        range.end()
+                    ));
+                }
+                Ok(())
+            }
+            Rule::Units(units) => {
+                // The longest unit that fits, so 160mm isn't read as 160m with an
+                // m left over.
+                let (unit, range) = units
+                    .iter()
+                    .filter(|(unit, _)| value.ends_with(unit.as_str()))
+                    .max_by_key(|(unit, _)| unit.len())
+                    .ok_or_else(|| {
+                        let names = units.iter().map(|(unit, _)| unit.as_str());
+                        format!(
+                            "{:?} needs a unit, one of {}",
+                            value,
+                            names.collect::<Vec<_>>().join(", ")
+                        )
+                    })?;
+                let n = number(&value[..value.len() - unit.len()])?;
+                if !range.contains(&n) {
+                    return Err(format!(
+                        "{}{} isn't between {}{} and {}{}",
+                        n,
+                        unit,
+                        range.start(),
+                        unit,
+                        range.end(),
+                        unit
+                    ));
+                }
+                Ok(())
+            }
+            Rule::Height(shortest, tallest) => Height::parse(value)?.between(shortest, tallest),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Rules {
+    required: Vec<String>,
+    fields: Vec<(String, Vec<Rule>)>,
+    pub duplicates: Policy,
+}
+
+impl Rules {
+    fn compile(profile: Profile) -> Result<Self, String> {
+        let fields = profile
+            .fields
+            .into_iter()
+            .map(|(name, field)| {
+                let rules = Rule::compile(&name, field)?;
+                Ok((name, rules))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Rules {
+            required: profile.required,
+            fields,
+            duplicates: profile.duplicates,
+        })
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, String> {
+        Self::compile(toml::from_str(s).map_err(|e| format!("bad rules: {}", e))?)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, String> {
+        Self::compile(serde_json::from_str(s).map_err(|e| format!("bad rules: {}", e))?)
+    }
+
+    // Files ending in .json are read as JSON, anything else as TOML.
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|_| format!("bad rules path, buddy: {}", path))?;
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json(&contents),
+            _ => Self::from_toml(&contents),
+        }
+        .map_err(|e| format!("{}: {}", path, e))
+    }
+
+    // The puzzle's rules.
+    pub fn default_profile() -> Result<Self, String> {
+        Self::from_toml(DEFAULT_PROFILE)
+    }
+
+    // Whether a value would pass the field's rules. Fields without rules take
+    // anything.
+    pub fn check_field(&self, name: &str, value: &str) -> Result<(), String> {
+        match self.fields.iter().find(|(field, _)| field == name) {
+            Some((_, rules)) => rules.iter().try_for_each(|rule| rule.check(value)),
+            None => Ok(()),
+        }
+    }
+
+    // Every value a field's one_of rules allow.
+    pub fn choices(&self, name: &str) -> Vec<&str> {
+        self.fields
+            .iter()
+            .filter(|(field, _)| field == name)
+            .flat_map(|(_, rules)| rules.iter())
+            .flat_map(|rule| match rule {
+                Rule::OneOf(choices) => choices.iter().map(String::as_str).collect(),
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    // The required fields that aren't there.
+    pub fn missing(&self, passport: &Passport) -> Vec<String> {
+        self.required
+            .iter()
+            .filter(|name| passport.get(name).is_none())
+            .cloned()
+            .collect()
+    }
+
+    // Every problem with the passport as (field, what's wrong), required fields
+    // first.
+    pub fn check(&self, passport: &Passport) -> Result<(), Vec<(String, String)>> {
+        let mut errors = self
+            .missing(passport)
+            .into_iter()
+            .map(|name| (name, String::from("missing")))
+            .collect::<Vec<_>>();
+        for (name, _) in self.fields.iter() {
+            if let Some(value) = passport.get(name) {
+                if let Err(e) = self.check_field(name, value) {
+                    errors.push((name.clone(), e));
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[test]
+fn default_profile_matches_the_typed_fields() -> Result<(), String> {
+    let rules = Rules::default_profile()?;
+    let input = fs::read_to_string("src/day_04_input.txt").map_err(|e| e.to_string())?;
+    for record in super::records(&input) {
+        let passport = super::passport(record.text)?;
+        assert_eq!(
+            rules.check(&passport).is_ok(),
+            passport.is_valid_2(),
+            "{}",
+            record.text
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn default_and_canonical_heights() -> Result<(), String> {
+    let puzzle = Rules::default_profile()?;
+    let canonical = Rules::from_file("src/day_04/canonical_rules.toml")?;
+    for (height, puzzle_allows, canonical_allows) in [
+        ("150cm", true, true),
+        ("76in", true, true),
+        ("149.9cm", false, true),
+        ("193.04cm", false, true),
+        ("1.5m", false, true),
+        ("5'0\"", false, true),
+        ("+150cm", false, false),
+        ("194cm", false, false),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            puzzle.check_field("hgt", height).is_ok(),
+            *puzzle_allows,
+            "{}",
+            height
+        );
+        assert_eq!(
+            canonical.check_field("hgt", height).is_ok(),
+            *canonical_allows,
+            "{}",
+            height
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn custom_profiles() -> Result<(), String> {
+    let rules = Rules::from_json(
+        r#"{
+            "required": ["hgt", "ecl"],
+            "fields": {
+                "hgt": { "units": { "mm": [1500, 1930] } },
+                "ecl": { "one_of": ["blu", "red"] },
+                "cid": { "pattern": "^[0-9]+$" }
+            }
+        }"#,
+    )?;
+    assert!(rules.check(&super::passport("hgt:1600mm ecl:red")?).is_ok());
+    assert_eq!(
+        rules
+            .check(&super::passport("hgt:160cm ecl:red cid:x")?)
+            .unwrap_err(),
+        vec![
+            (
+                String::from("cid"),
+                String::from("\"x\" doesn't match ^[0-9]+$")
+            ),
+            (
+                String::from("hgt"),
+                String::from("\"160cm\" needs a unit, one of mm")
+            ),
+        ]
+    );
+    assert_eq!(
+        rules.check(&super::passport("hgt:1000mm")?).unwrap_err(),
+        vec![
+            (String::from("ecl"), String::from("missing")),
+            (
+                String::from("hgt"),
+                String::from("1000mm isn't between 1500mm and 1930mm")
+            ),
+        ]
+    );
+
+    let rules = Rules::from_toml("[fields.hgt.units]\nm = [1, 2]\nmm = [1500, 1930]")?;
+    assert!(rules.check(&super::passport("hgt:1600mm")?).is_ok());
+    assert!(rules.check(&super::passport("hgt:2m")?).is_ok());
+    assert!(rules.check(&super::passport("hgt:3m")?).is_err());
+
+    assert!(Rules::from_toml("[fields.byr]\nrange = [2002, 1920]").is_err());
+    assert!(Rules::from_toml("[fields.byr]\npattern = \"(\"").is_err());
+    assert!(Rules::from_toml("[fields.byr]\nbetween = [1, 2]").is_err());
+    Ok(())
+}
@@ -0,0 +1,105 @@
+// Guesses at what a bad value was meant to be. These only ever end up in the
+// report, nothing here changes a passport.
+use serde::Serialize;
+
+use super::rules::Rules;
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Suggestion {
+    // The one value close to what was written that would pass.
+    Replace(String),
+    // Something that might help when there's no single fix, like a number
+    // that's a digit short.
+    Hint(String),
+}
+
+// Whether the letters of `short` show up in order in `long`, starting with
+// the same one, like blu in blue or hzl in hazel.
+fn abbreviates(short: &str, long: &str) -> bool {
+    let mut letters = long.chars();
+    short.chars().next() == long.chars().next()
+        && short.chars().all(|c| letters.any(|letter| letter == c))
+}
+
+fn candidates(rules: &Rules, field: &str, value: &str) -> Vec<String> {
+    let lower = value.to_lowercase();
+    let mut candidates = vec![lower.clone(), format!("#{}", lower)];
+    if value.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        for unit in ["cm", "in", "mm", "m"].iter() {
+            candidates.push(format!("{}{}", value, unit));
+        }
+    }
+    for choice in rules.choices(field) {
+        if abbreviates(choice, &lower) {
+            candidates.push(String::from(choice));
+        }
+    }
+    candidates
+}
+
+// Digits that only need padding or trimming to fit.
+fn digit_hint(rules: &Rules, field: &str, value: &str) -> Option<Suggestion> {
+    if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    for n in 1..=3 {
+        if rules
+            .check_field(field, &format!("{}{}", "0".repeat(n), value))
+            .is_ok()
+        {
+            return Some(Suggestion::Hint(format!("{} digit{} short", n, plural(n))));
+        }
+        if value.len() > n && rules.check_field(field, &value[n..]).is_ok() {
+            return Some(Suggestion::Hint(format!(
+                "{} digit{} too many",
+                n,
+                plural(n)
+            )));
+        }
+    }
+    None
+}
+
+pub fn suggest(rules: &Rules, field: &str, value: &str) -> Option<Suggestion> {
+    let mut fixes = candidates(rules, field, value)
+        .into_iter()
+        .filter(|candidate| candidate != value)
+        .filter(|candidate| rules.check_field(field, candidate).is_ok())
+        .collect::<Vec<_>>();
+    fixes.sort();
+    fixes.dedup();
+    match fixes.len() {
+        1 => fixes.pop().map(Suggestion::Replace),
+        _ => digit_hint(rules, field, value),
+    }
+}
+
+#[test]
+fn suggestions() -> Result<(), String> {
+    let rules = Rules::default_profile()?;
+    let suggestion = |field, value| suggest(&rules, field, value);
+    let replace = |value: &str| Some(Suggestion::Replace(String::from(value)));
+    assert_eq!(suggestion("hcl", "123abc"), replace("#123abc"));
+    assert_eq!(suggestion("hcl", "#123ABC"), replace("#123abc"));
+    assert_eq!(suggestion("ecl", "blue"), replace("blu"));
+    assert_eq!(suggestion("ecl", "Hazel"), replace("hzl"));
+    assert_eq!(suggestion("hgt", "170"), replace("170cm"));
+    assert_eq!(suggestion("hgt", "60"), replace("60in"));
+    assert_eq!(
+        suggestion("pid", "12345678"),
+        Some(Suggestion::Hint(String::from("1 digit short")))
+    );
+    assert_eq!(
+        suggestion("pid", "01234567890"),
+        Some(Suggestion::Hint(String::from("2 digits too many")))
+    );
+    assert_eq!(suggestion("byr", "1900"), None);
+    assert_eq!(suggestion("ecl", "zzz"), None);
+
+    // 1.8m or 1.8mm? Only one of them is a person.
+    let rules = Rules::from_file("src/day_04/canonical_rules.toml")?;
+    assert_eq!(suggest(&rules, "hgt", "1.8"), replace("1.8m"));
+    Ok(())
+}
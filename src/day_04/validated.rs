@@ -0,0 +1,301 @@
+use std::{convert::TryFrom, fmt, ops::RangeInclusive};
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    bytes::complete::take_while_m_n,
+    combinator::{all_consuming, value},
+    sequence::tuple,
+    IResult,
+};
+use serde::{Serialize, Serializer};
+
+use super::{height::Height, Passport};
+
+// For fields that are written out the way the puzzle writes them.
+fn written<T: fmt::Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Year(u16);
+
+impl Year {
+    fn parse_within(s: &str, range: RangeInclusive<u16>) -> Result<Self, String> {
+        if s.len() != 4 || !s.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("{:?} isn't a four digit year", s));
+        }
+        let year = s.parse().map_err(|_| format!("{:?} isn't a year", s))?;
+        if !range.contains(&year) {
+            return Err(format!(
+                "{} isn't between {} and {}",
+                year,
+                range.start(),
+                range.end()
+            ));
+        }
+        Ok(Year(year))
+    }
+}
+
+impl fmt::Display for Year {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+fn is_hex_digit(c: char) -> bool {
+    c.is_ascii_hexdigit() && !c.is_ascii_uppercase()
+}
+
+fn hex(input: &str) -> IResult<&str, (&str, &str)> {
+    all_consuming(tuple((tag("#"), (take_while_m_n)(6, 6, is_hex_digit))))(input)
+}
+
+#[test]
+fn hex_works() {
+    let (_, actual) = hex("#abcdef").unwrap();
+    assert_eq!(actual, ("#", "abcdef"));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb {
+    red: u8,
+    green: u8,
+    blue: u8,
+}
+
+impl TryFrom<&str> for Rgb {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let (_, (_, digits)) =
+            hex(s).map_err(|_| format!("{:?} isn't # and six lowercase hex digits", s))?;
+        let channel = |idx: usize| u8::from_str_radix(&digits[idx..idx + 2], 16).unwrap();
+        Ok(Rgb {
+            red: channel(0),
+            green: channel(2),
+            blue: channel(4),
+        })
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EyeColor {
+    Amber,
+    Blue,
+    Brown,
+    Gray,
+    Green,
+    Hazel,
+    Other,
+}
+
+fn eye_color(input: &str) -> IResult<&str, EyeColor> {
+    alt((
+        value(EyeColor::Amber, tag("amb")),
+        value(EyeColor::Blue, tag("blu")),
+        value(EyeColor::Brown, tag("brn")),
+        value(EyeColor::Gray, tag("gry")),
+        value(EyeColor::Green, tag("grn")),
+        value(EyeColor::Hazel, tag("hzl")),
+        value(EyeColor::Other, tag("oth")),
+    ))(input)
+}
+
+impl TryFrom<&str> for EyeColor {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let (_, color) = all_consuming(eye_color)(s)
+            .map_err(|_| format!("{:?} isn't one of amb, blu, brn, gry, grn, hzl or oth", s))?;
+        Ok(color)
+    }
+}
+
+impl fmt::Display for EyeColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let color = match self {
+            EyeColor::Amber => "amb",
+            EyeColor::Blue => "blu",
+            EyeColor::Brown => "brn",
+            EyeColor::Gray => "gry",
+            EyeColor::Green => "grn",
+            EyeColor::Hazel => "hzl",
+            EyeColor::Other => "oth",
+        };
+        write!(f, "{}", color)
+    }
+}
+
+// Kept as a string so leading zeros survive.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PassportId(String);
+
+impl TryFrom<&str> for PassportId {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        if s.len() != 9 || !s.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("{:?} isn't nine digits", s));
+        }
+        Ok(PassportId(String::from(s)))
+    }
+}
+
+impl fmt::Display for PassportId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// A passport that passed every part 2 rule.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidPassport {
+    byr: Year,
+    iyr: Year,
+    eyr: Year,
+    #[serde(serialize_with = "written")]
+    hgt: Height,
+    #[serde(serialize_with = "written")]
+    hcl: Rgb,
+    #[serde(serialize_with = "written")]
+    ecl: EyeColor,
+    pid: PassportId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cid: Option<String>,
+}
+
+// The field name and what's wrong with it.
+pub type FieldErrors = Vec<(&'static str, String)>;
+
+fn convert<'a, T>(
+    name: &'static str,
+    value: &'a Option<String>,
+    convert: impl Fn(&'a str) -> Result<T, String>,
+    errors: &mut FieldErrors,
+) -> Option<T> {
+    let converted = value
+        .as_deref()
+        .ok_or_else(|| String::from("missing"))
+        .and_then(convert);
+    match converted {
+        Ok(converted) => Some(converted),
+        Err(e) => {
+            errors.push((name, e));
+            None
+        }
+    }
+}
+
+impl TryFrom<&Passport> for ValidPassport {
+    type Error = FieldErrors;
+
+    // Checks every field rather than stopping at the first bad one, so the
+    // error lists everything that's wrong.
+    fn try_from(passport: &Passport) -> Result<Self, Self::Error> {
+        let mut errors = vec![];
+        let byr = convert(
+            "byr",
+            &passport.byr,
+            |s| Year::parse_within(s, 1920..=2002),
+            &mut errors,
+        );
+        let iyr = convert(
+            "iyr",
+            &passport.iyr,
+            |s| Year::parse_within(s, 2010..=2020),
+            &mut errors,
+        );
+        let eyr = convert(
+            "eyr",
+            &passport.eyr,
+            |s| Year::parse_within(s, 2020..=2030),
+            &mut errors,
+        );
+        let hgt = convert("hgt", &passport.hgt, Height::try_from, &mut errors);
+        let hcl = convert("hcl", &passport.hcl, Rgb::try_from, &mut errors);
+        let ecl = convert("ecl", &passport.ecl, EyeColor::try_from, &mut errors);
+        let pid = convert("pid", &passport.pid, PassportId::try_from, &mut errors);
+        match (byr, iyr, eyr, hgt, hcl, ecl, pid) {
+            (Some(byr), Some(iyr), Some(eyr), Some(hgt), Some(hcl), Some(ecl), Some(pid)) => {
+                Ok(ValidPassport {
+                    byr,
+                    iyr,
+                    eyr,
+                    hgt,
+                    hcl,
+                    ecl,
+                    pid,
+                    cid: passport.cid.clone(),
+                })
+            }
+            _ => Err(errors),
+        }
+    }
+}
+
+// Prints the passport back out as a single line record.
+impl fmt::Display for ValidPassport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "byr:{} iyr:{} eyr:{} hgt:{} hcl:{} ecl:{} pid:{}",
+            self.byr, self.iyr, self.eyr, self.hgt, self.hcl, self.ecl, self.pid
+        )?;
+        if let Some(cid) = &self.cid {
+            write!(f, " cid:{}", cid)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn field_conversions() {
+    assert_eq!(Year::parse_within("2002", 1920..=2002), Ok(Year(2002)));
+    assert!(Year::parse_within("2003", 1920..=2002).is_err());
+    assert!(Year::parse_within("+200", 1920..=2002).is_err());
+    assert_eq!(
+        Rgb::try_from("#123abc"),
+        Ok(Rgb {
+            red: 0x12,
+            green: 0x3a,
+            blue: 0xbc
+        })
+    );
+    assert!(Rgb::try_from("#123abz").is_err());
+    assert!(Rgb::try_from("123abc").is_err());
+    assert_eq!(EyeColor::try_from("brn"), Ok(EyeColor::Brown));
+    assert!(EyeColor::try_from("wat").is_err());
+    assert!(EyeColor::try_from("blux").is_err());
+    assert!(PassportId::try_from("000000001").is_ok());
+    assert!(PassportId::try_from("0123456789").is_err());
+    assert!(PassportId::try_from("+12345678").is_err());
+}
+
+#[test]
+fn valid_passport_lists_every_problem() -> Result<(), String> {
+    let passport = super::passport("eyr:1972 cid:100 hcl:#18171d ecl:amb hgt:170 pid:186cm")?;
+    let errors = ValidPassport::try_from(&passport).unwrap_err();
+    assert_eq!(
+        errors.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+        vec!["byr", "iyr", "eyr", "hgt", "pid"]
+    );
+    assert_eq!(errors[0].1, "missing");
+
+    let passport =
+        super::passport("pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f")?;
+    assert_eq!(
+        ValidPassport::try_from(&passport)
+            .map_err(|e| format!("{:?}", e))?
+            .to_string(),
+        "byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704"
+    );
+    Ok(())
+}
@@ -1,59 +1,217 @@
-use std::fs;
+use std::{fs, ops::RangeInclusive};
 
 use nom::{
     bytes::complete::take_while_m_n,
-    combinator::{all_consuming, map, map_opt},
+    combinator::{all_consuming, map},
     sequence::tuple,
     IResult,
 };
 
-trait CanBinary: Sized {
-    fn from_char(c: char) -> Option<char>;
-    fn from_str(s: &str) -> Option<usize> {
-        s.chars()
-            .map(Self::from_char)
-            .collect::<Option<String>>()
-            .and_then(|s| usize::from_str_radix(&s, 2).ok())
+use crate::cli::Flags;
+
+// The two letters that stand for 0 (the front or left half) and 1 (the back or
+// right half).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Alphabet {
+    zero: char,
+    one: char,
+}
+
+impl Alphabet {
+    fn digit(&self, c: char) -> Option<usize> {
+        match c {
+            c if c == self.zero => Some(0),
+            c if c == self.one => Some(1),
+            _ => None,
+        }
+    }
+
+    fn letter(&self, bit: usize) -> char {
+        if bit == 0 {
+            self.zero
+        } else {
+            self.one
+        }
     }
 }
 
-struct BinaryFB;
+// Written as the 0 letter then the 1 letter, like FB.
+impl std::str::FromStr for Alphabet {
+    type Err = String;
 
-impl CanBinary for BinaryFB {
-    fn from_char(c: char) -> Option<char> {
-        Some(match c {
-            'F' => '0',
-            'B' => '1',
-            _ => return None,
-        })
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.chars().collect::<Vec<_>>()[..] {
+            [zero, one] if zero != one => Ok(Alphabet { zero, one }),
+            _ => Err(format!(
+                "An alphabet is two different letters, like FB, not {}",
+                s
+            )),
+        }
     }
 }
 
-struct BinaryRL;
+// How many rows and columns a plane has, as the number of letters a boarding
+// pass uses for each, and which letters those are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Layout {
+    row_bits: u32,
+    column_bits: u32,
+    rows: Alphabet,
+    columns: Alphabet,
+}
 
-impl CanBinary for BinaryRL {
-    fn from_char(c: char) -> Option<char> {
-        Some(match c {
-            'R' => '1',
-            'L' => '0',
-            _ => return None,
-        })
+impl Default for Layout {
+    // The puzzle's plane: 128 rows of 8 seats.
+    fn default() -> Self {
+        Layout {
+            row_bits: 7,
+            column_bits: 3,
+            rows: Alphabet {
+                zero: 'F',
+                one: 'B',
+            },
+            columns: Alphabet {
+                zero: 'L',
+                one: 'R',
+            },
+        }
     }
 }
 
-fn bad_binary<T: CanBinary>(input: &str, length: usize) -> IResult<&str, usize> {
-    map_opt(
-        take_while_m_n(length, length, |c: char| T::from_char(c).is_some()),
-        T::from_str,
+fn binary(input: &str, alphabet: Alphabet, bits: u32) -> IResult<&str, usize> {
+    map(
+        take_while_m_n(bits as usize, bits as usize, |c: char| {
+            alphabet.digit(c).is_some()
+        }),
+        |digits: &str| {
+            digits
+                .chars()
+                .filter_map(|c| alphabet.digit(c))
+                .fold(0, |n, digit| n * 2 + digit)
+        },
     )(input)
 }
 
-fn row(input: &str) -> IResult<&str, usize> {
-    bad_binary::<BinaryFB>(input, 7)
-}
+impl Layout {
+    fn new(
+        row_bits: u32,
+        column_bits: u32,
+        rows: Alphabet,
+        columns: Alphabet,
+    ) -> Result<Self, String> {
+        // Keeps every seat ID inside a u32, and so a usize.
+        if row_bits
+            .checked_add(column_bits)
+            .is_none_or(|bits| bits > 32)
+        {
+            return Err(format!(
+                "{} row and {} column letters is too big a plane, buddy",
+                row_bits, column_bits
+            ));
+        }
+        Ok(Layout {
+            row_bits,
+            column_bits,
+            rows,
+            columns,
+        })
+    }
+
+    fn from_flags(flags: &Flags) -> Result<Self, String> {
+        let default = Layout::default();
+        let bits = |name: &str, default: u32| -> Result<u32, String> {
+            match flags.value(name)? {
+                Some(n) => n
+                    .parse()
+                    .map_err(|_| format!("--{} should be a number, not {}", name, n)),
+                None => Ok(default),
+            }
+        };
+        let alphabet = |name: &str, default: Alphabet| -> Result<Alphabet, String> {
+            match flags.value(name)? {
+                Some(letters) => letters.parse(),
+                None => Ok(default),
+            }
+        };
+        Layout::new(
+            bits("row-bits", default.row_bits)?,
+            bits("column-bits", default.column_bits)?,
+            alphabet("row-letters", default.rows)?,
+            alphabet("column-letters", default.columns)?,
+        )
+    }
+
+    fn row_count(&self) -> usize {
+        1 << self.row_bits
+    }
 
-fn column(input: &str) -> IResult<&str, usize> {
-    bad_binary::<BinaryRL>(input, 3)
+    fn column_count(&self) -> usize {
+        1 << self.column_bits
+    }
+
+    // Seat IDs count across each row before moving back to the next one.
+    fn id(&self, seat: &Seat) -> usize {
+        seat.row * self.column_count() + seat.column
+    }
+
+    fn ids(&self) -> RangeInclusive<usize> {
+        0..=self.row_count() * self.column_count() - 1
+    }
+
+    fn seat_with_id(&self, id: usize) -> Result<Seat, String> {
+        if !self.ids().contains(&id) {
+            return Err(format!(
+                "There's no seat {} on this plane, buddy, only {:?}",
+                id,
+                self.ids()
+            ));
+        }
+        Ok(Seat {
+            row: id / self.column_count(),
+            column: id % self.column_count(),
+        })
+    }
+
+    // The boarding pass that `seat` decodes back into this seat.
+    fn encode(&self, seat: &Seat) -> Result<String, String> {
+        if seat.row >= self.row_count() || seat.column >= self.column_count() {
+            return Err(format!(
+                "There's no row {} seat {} on this plane, buddy",
+                seat.row, seat.column
+            ));
+        }
+        let letters = |n: usize, bits: u32, alphabet: Alphabet| {
+            (0..bits)
+                .rev()
+                .map(move |bit| alphabet.letter((n >> bit) & 1))
+        };
+        Ok(letters(seat.row, self.row_bits, self.rows)
+            .chain(letters(seat.column, self.column_bits, self.columns))
+            .collect())
+    }
+
+    fn seat(&self, input: &str) -> Result<Seat, String> {
+        let (_, seat) = all_consuming(map(
+            tuple((
+                |i| binary(i, self.rows, self.row_bits),
+                |i| binary(i, self.columns, self.column_bits),
+            )),
+            |(row, column)| Seat { row, column },
+        ))(input)
+        .map_err(|_| {
+            format!(
+                "{:?} isn't {} of {}{} then {} of {}{}",
+                input,
+                self.row_bits,
+                self.rows.zero,
+                self.rows.one,
+                self.column_bits,
+                self.columns.zero,
+                self.columns.one
+            )
+        })?;
+        Ok(seat)
+    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -62,40 +220,26 @@ struct Seat {
     column: usize,
 }
 
-impl Seat {
-    fn id(&self) -> usize {
-        self.row * 8 + self.column
-    }
-}
-
-fn seat(input: &str) -> Result<Seat, String> {
-    let (_, seat) = all_consuming(map(tuple((row, column)), |(row, column)| Seat {
-        row,
-        column,
-    }))(input)
-    .map_err(|s| format!("{}", s))?;
-    Ok(seat)
-}
-
 #[test]
 fn parse_seat() -> Result<(), String> {
+    let layout = Layout::default();
     let input = "FBFBBFFRLR";
-    let s = seat(input)?;
+    let s = layout.seat(input)?;
     assert_eq!(s, Seat { row: 44, column: 5 });
-    assert_eq!(s.id(), 357);
+    assert_eq!(layout.id(&s), 357);
 
     let input = "BFFFBBFRRR";
-    let s = seat(input)?;
+    let s = layout.seat(input)?;
     assert_eq!(s, Seat { row: 70, column: 7 });
-    assert_eq!(s.id(), 567);
+    assert_eq!(layout.id(&s), 567);
 
     let input = "FFFBBBFRRR";
-    let s = seat(input)?;
+    let s = layout.seat(input)?;
     assert_eq!(s, Seat { row: 14, column: 7 });
-    assert_eq!(s.id(), 119);
+    assert_eq!(layout.id(&s), 119);
 
     let input = "BBFFBBFRLL";
-    let s = seat(input)?;
+    let s = layout.seat(input)?;
     assert_eq!(
         s,
         Seat {
@@ -103,48 +247,160 @@ fn parse_seat() -> Result<(), String> {
             column: 4
         }
     );
-    assert_eq!(s.id(), 820);
+    assert_eq!(layout.id(&s), 820);
 
     Ok(())
 }
 
-pub fn part1() -> Result<(), String> {
+#[test]
+fn other_layouts() -> Result<(), String> {
+    // 16 rows of 4 seats, with the letters swapped around.
+    let layout = Layout::new(4, 2, "BF".parse()?, "RL".parse()?)?;
+    let s = layout.seat("FBBFLR")?;
+    assert_eq!(s, Seat { row: 9, column: 2 });
+    assert_eq!(layout.id(&s), 38);
+    assert_eq!(layout.ids(), 0..=63);
+    assert!(layout.seat("FBBFLRL").is_err());
+    assert!(layout.seat("FBBFFR").is_err());
+
+    assert_eq!(Layout::default().ids(), 0..=1023);
+    assert!("FF".parse::<Alphabet>().is_err());
+    assert!(Layout::new(30, 3, "FB".parse()?, "LR".parse()?).is_err());
+    assert!(Layout::new(u32::MAX, 1, "FB".parse()?, "LR".parse()?).is_err());
+    Ok(())
+}
+
+#[test]
+fn encoding_round_trips() -> Result<(), String> {
+    let layouts = [
+        Layout::default(),
+        Layout::new(4, 2, "BF".parse()?, "RL".parse()?)?,
+        Layout::new(3, 0, "01".parse()?, "LR".parse()?)?,
+    ];
+    // Small enough to try every seat.
+    for layout in layouts.iter() {
+        for id in layout.ids() {
+            let seat = layout.seat_with_id(id)?;
+            let pass = layout.encode(&seat)?;
+            assert_eq!(pass.len() as u32, layout.row_bits + layout.column_bits);
+            assert_eq!(layout.seat(&pass)?, seat);
+            assert_eq!(layout.id(&seat), id);
+            assert_eq!(layout.encode(&layout.seat(&pass)?)?, pass);
+        }
+    }
+
+    let layout = Layout::default();
+    assert_eq!(layout.encode(&Seat { row: 44, column: 5 })?, "FBFBBFFRLR");
+    assert_eq!(layout.encode(&layout.seat_with_id(820)?)?, "BBFFBBFRLL");
+    assert!(layout.seat_with_id(1024).is_err());
+    assert!(layout
+        .encode(&Seat {
+            row: 128,
+            column: 0
+        })
+        .is_err());
+    assert!(layout.encode(&Seat { row: 0, column: 8 }).is_err());
+    Ok(())
+}
+
+// Each --id, then each --seat written as row,column.
+fn encode_with(flags: &Flags) -> Result<(), String> {
+    let layout = Layout::from_flags(flags)?;
+    let mut seats = vec![];
+    for id in flags.values("id") {
+        let id = id?;
+        let id = id
+            .parse()
+            .map_err(|_| format!("Seat IDs are numbers, buddy, not {}", id))?;
+        seats.push(layout.seat_with_id(id)?);
+    }
+    for seat in flags.values("seat") {
+        let seat = seat?;
+        let number = |n: &str| n.trim().parse::<usize>().ok();
+        let (row, column) = seat
+            .split_once(',')
+            .and_then(|(row, column)| Some((number(row)?, number(column)?)))
+            .ok_or_else(|| format!("Seats look like 44,5 buddy, not {}", seat))?;
+        seats.push(Seat { row, column });
+    }
+    if seats.is_empty() {
+        return Err(String::from(
+            "Give me an --id or a --seat to encode, buddy.",
+        ));
+    }
+    for seat in seats.iter() {
+        println!(
+            "{}: row {}, column {}, ID {}",
+            layout.encode(seat)?,
+            seat.row,
+            seat.column,
+            layout.id(seat)
+        );
+    }
+    Ok(())
+}
+
+fn seat_ids(layout: &Layout, flags: &Flags) -> Result<Vec<usize>, String> {
+    let path = flags.value("passes")?.unwrap_or("src/day_05_input.txt");
     let input =
-        fs::read_to_string("src/day_05_input.txt").map_err(|_| String::from("bad path, buddy."))?;
-    let largest_id = input
-        .trim()
-        .split("\n")
-        .map(seat)
-        .fold(Ok(0 as usize), |a, b| {
-            b.map_err(|e| format!("{}", e))
-                .and_then(|b| Ok(b.id().max(a?)))
-        })?;
+        fs::read_to_string(path).map_err(|_| format!("bad boarding pass path, buddy: {}", path))?;
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| layout.seat(line).map(|seat| layout.id(&seat)))
+        .collect()
+}
+
+const FLAGS: [&str; 4] = ["row-bits", "column-bits", "row-letters", "column-letters"];
+
+pub fn cli(args: &[String]) -> Result<(), String> {
+    match args.split_first() {
+        None => {
+            part1()?;
+            part2()
+        }
+        Some((command, rest)) => {
+            let flags = |extra: &[&str]| Flags::parse(rest)?.only(&[&FLAGS[..], extra].concat());
+            match command.as_str() {
+                "part1" => part1_with(&flags(&["passes"])?),
+                "part2" => part2_with(&flags(&["passes"])?),
+                "encode" => encode_with(&flags(&["id", "seat"])?),
+                _ => Err(format!("Unknown day 5 command: {}", command)),
+            }
+        }
+    }
+}
+
+pub fn part1() -> Result<(), String> {
+    part1_with(&Flags::parse(&[])?)
+}
+
+fn part1_with(flags: &Flags) -> Result<(), String> {
+    let layout = Layout::from_flags(flags)?;
+    let largest_id = seat_ids(&layout, flags)?
+        .into_iter()
+        .max()
+        .ok_or(String::from("There's a goof in here"))?;
     println!("Part 1: {}", largest_id);
     Ok(())
 }
 
 pub fn part2() -> Result<(), String> {
-    let input =
-        fs::read_to_string("src/day_05_input.txt").map_err(|_| String::from("bad path, buddy."))?;
-    let mut seats = input
-        .trim()
-        .split("\n")
-        .map(seat)
-        .collect::<Result<Vec<_>, String>>()?;
-    seats.sort_by_key(|s| s.id());
-    let mut seats = seats.iter();
-    let mut your_id = seats
-        .next()
-        .map(Seat::id)
+    part2_with(&Flags::parse(&[])?)
+}
+
+// Your seat is the one missing ID with taken seats on both sides of it.
+fn part2_with(flags: &Flags) -> Result<(), String> {
+    let layout = Layout::from_flags(flags)?;
+    let mut seats = seat_ids(&layout, flags)?;
+    seats.sort_unstable();
+    let your_id = seats
+        .windows(2)
+        .find(|pair| pair[0] + 2 == pair[1])
+        .map(|pair| pair[0] + 1)
+        .filter(|id| layout.ids().contains(id))
         .ok_or(String::from("There's a goof in here"))?;
-    for seat in seats {
-        let this_seat = seat.id();
-        if your_id + 1 != this_seat {
-            your_id = your_id + 1;
-            break;
-        }
-        your_id = this_seat
-    }
     println!("Part 2: {}", your_id);
     Ok(())
 }
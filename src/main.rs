@@ -1,3 +1,6 @@
+use std::env;
+
+mod cli;
 mod day_01;
 mod day_02;
 mod day_03;
@@ -5,6 +8,19 @@ mod day_04;
 mod day_05;
 
 fn main() -> Result<(), String> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match args.split_first() {
+        None => all_days(),
+        Some((day, rest)) => match day.as_str() {
+            "day03" => day_03::cli(rest),
+            "day04" => day_04::cli(rest),
+            "day05" => day_05::cli(rest),
+            _ => Err(format!("Don't know how to run {}, buddy.", day)),
+        },
+    }
+}
+
+fn all_days() -> Result<(), String> {
     println!("Day 01");
     day_01::part1()?;
     day_01::part2()?;